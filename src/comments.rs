@@ -0,0 +1,265 @@
+use crate::{
+    Result,
+    hidden::{attr_error, sheet_id, sheet_path, xml_error, xml_reader},
+    utils::WorkbookBytes,
+};
+use quick_xml::{
+    escape::resolve_predefined_entity,
+    events::{BytesStart, Event},
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Cursor, Read, Seek},
+    ops::RangeInclusive,
+    path::Path,
+};
+use zip::ZipArchive;
+
+/// Notes on the cells of the header `rows` of `sheet`, by column position
+/// counted from `start_col`. Notes of several header rows of one column are
+/// joined by a line break.
+///
+/// Like hidden columns, notes are read from the xlsx parts since calamine
+/// does not report them, and other formats have none.
+pub(crate) fn header_comments(
+    path: &Path,
+    bytes: Option<&WorkbookBytes>,
+    sheet: &str,
+    rows: RangeInclusive<u32>,
+    start_col: u32,
+) -> Result<HashMap<usize, String>> {
+    let mut comments = match bytes {
+        Some(bytes) => read_comments(Cursor::new(bytes.0.clone()), sheet)?,
+        None => read_comments(File::open(path)?, sheet)?,
+    };
+    comments.sort_by_key(|&((row, _), _)| row);
+
+    let mut header = HashMap::<usize, String>::new();
+    for ((row, col), text) in comments {
+        let Some(col) = col.checked_sub(start_col) else {
+            continue;
+        };
+        if !rows.contains(&row) || text.is_empty() {
+            continue;
+        }
+        header
+            .entry(col as usize)
+            .and_modify(|comment| {
+                comment.push('\n');
+                comment.push_str(&text);
+            })
+            .or_insert(text);
+    }
+    Ok(header)
+}
+
+fn read_comments<RS: Read + Seek>(reader: RS, sheet: &str) -> Result<Vec<((u32, u32), String)>> {
+    let Ok(mut zip) = ZipArchive::new(reader) else {
+        return Ok(Vec::new());
+    };
+    let Some(id) = sheet_id(&mut zip, sheet)? else {
+        return Ok(Vec::new());
+    };
+    let Some(path) = sheet_path(&mut zip, &id)? else {
+        return Ok(Vec::new());
+    };
+    let Some(path) = comments_path(&mut zip, &path)? else {
+        return Ok(Vec::new());
+    };
+    let Some(mut xml) = xml_reader(&mut zip, &path)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut authors = Vec::new();
+    let mut comments = Vec::new();
+    let mut comment = None;
+    let mut text = String::new();
+    let mut in_author = false;
+    let mut in_text = false;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(e) if e.local_name().as_ref() == b"author" => {
+                in_author = true;
+                authors.push(String::new());
+            }
+            Event::End(e) if e.local_name().as_ref() == b"author" => in_author = false,
+            Event::Start(e) if e.local_name().as_ref() == b"comment" => {
+                let value =
+                    |name: &str| -> Result<Option<String>> { attribute(&e, name, xml.decoder()) };
+                let position = value("ref")?.as_deref().and_then(cell_position);
+                let author = value("authorId")?.and_then(|id| id.parse::<usize>().ok());
+                comment = Some((position, author));
+                text.clear();
+            }
+            Event::End(e) if e.local_name().as_ref() == b"comment" => {
+                if let Some((Some(position), author)) = comment.take() {
+                    let author = author.and_then(|id| authors.get(id));
+                    comments.push((position, comment_text(&text, author)));
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) if e.local_name().as_ref() == b"t" => in_text = false,
+            Event::Text(e) if in_text || in_author => {
+                let content = e
+                    .xml_content()
+                    .map_err(|e| xml_error(quick_xml::Error::from(e)))?;
+                push_text(in_text, &mut text, &mut authors, &content);
+            }
+            Event::GeneralRef(e) if in_text || in_author => {
+                let content = match e.resolve_char_ref().map_err(xml_error)? {
+                    Some(c) => c.to_string(),
+                    None => {
+                        let name = e
+                            .decode()
+                            .map_err(|e| xml_error(quick_xml::Error::from(e)))?;
+                        resolve_predefined_entity(&name)
+                            .unwrap_or_default()
+                            .to_owned()
+                    }
+                };
+                push_text(in_text, &mut text, &mut authors, &content);
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(comments)
+}
+
+fn push_text(in_text: bool, text: &mut String, authors: &mut [String], content: &str) {
+    if in_text {
+        text.push_str(content);
+    } else if let Some(author) = authors.last_mut() {
+        author.push_str(content);
+    }
+}
+
+// Excel starts a note with a bold `Author:` line, which is not part of what
+// the note says about the column.
+fn comment_text(text: &str, author: Option<&String>) -> String {
+    let text = author
+        .filter(|author| !author.is_empty())
+        .and_then(|author| text.strip_prefix(author.as_str()))
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(text);
+    text.trim().to_owned()
+}
+
+fn attribute(
+    e: &BytesStart,
+    name: &str,
+    decoder: quick_xml::encoding::Decoder,
+) -> Result<Option<String>> {
+    let attribute = e.try_get_attribute(name).map_err(attr_error)?;
+    Ok(attribute
+        .map(|a| a.decode_and_unescape_value(decoder))
+        .transpose()
+        .map_err(xml_error)?
+        .map(|v| v.into_owned()))
+}
+
+// The comments part of the sheet at `path`, from the sheet's own rels.
+fn comments_path<RS: Read + Seek>(zip: &mut ZipArchive<RS>, path: &str) -> Result<Option<String>> {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let rels = format!("{}/_rels/{}.rels", dir, file);
+    let Some(mut xml) = xml_reader(zip, &rels)? else {
+        return Ok(None);
+    };
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                let is_comments = attribute(&e, "Type", xml.decoder())?
+                    .is_some_and(|kind| kind.ends_with("/comments"));
+                if !is_comments {
+                    continue;
+                }
+                return Ok(attribute(&e, "Target", xml.decoder())?
+                    .map(|target| resolve_target(dir, &target)));
+            }
+            Event::Eof => return Ok(None),
+            _ => (),
+        }
+    }
+}
+
+// Resolves a relationship target such as `../comments1.xml` against `dir`.
+fn resolve_target(dir: &str, target: &str) -> String {
+    if let Some(path) = target.strip_prefix('/') {
+        return path.to_owned();
+    }
+    let mut parts = dir.split('/').filter(|p| !p.is_empty()).collect::<Vec<_>>();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+// Zero-based row and column of an A1 reference.
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.bytes().all(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    let col = letters.bytes().try_fold(0u32, |col, b| {
+        col.checked_mul(26)?.checked_add((b - b'A') as u32 + 1)
+    })?;
+    let row = digits.parse::<u32>().ok()?.checked_sub(1)?;
+    Some((row, col - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_comments() {
+        let path = Path::new("tests/fixtures/comments.xlsx");
+        let comments = header_comments(path, None, "Sheet1", 0..=0, 0).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[&0], "Customer number & key");
+        assert_eq!(comments[&2], "Net amount in EUR");
+        let comments = header_comments(path, None, "Sheet1", 0..=0, 1).unwrap();
+        assert_eq!(
+            comments.get(&1).map(String::as_str),
+            Some("Net amount in EUR")
+        );
+        let comments = header_comments(path, None, "Sheet1", 0..=1, 0).unwrap();
+        assert_eq!(comments[&1], "Not a header");
+        assert!(
+            header_comments(path, None, "Sheet1", 2..=2, 0)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            header_comments(
+                Path::new("tests/fixtures/simple.xlsb"),
+                None,
+                "Sheet1",
+                0..=0,
+                0
+            )
+            .unwrap()
+            .is_empty()
+        );
+
+        assert_eq!(cell_position("B1"), Some((0, 1)));
+        assert_eq!(cell_position("AA10"), Some((9, 26)));
+        assert_eq!(cell_position("1A"), None);
+        assert_eq!(
+            resolve_target("xl/worksheets", "../comments1.xml"),
+            "xl/comments1.xml"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    Error, Result,
+    config::{ConnectOptions, REDACTED},
+    log::warning,
+};
+use sqlx::{AnyPool, Connection, Executor, PgConnection};
+
+/// A Postgres connection of its own for `COPY FROM STDIN`, which the Any
+/// driver cannot run. A COPY load sets up the table, copies the rows and
+/// indexes them all on this connection, in one transaction committed by
+/// [`commit`](Self::commit) when the load is transactional.
+pub(crate) struct PgCopy {
+    conn: PgConnection,
+    transactional: bool,
+}
+
+impl PgCopy {
+    /// Connects to the database of `pool` with the retries and the acquire
+    /// timeout of `connect_options`.
+    pub(crate) async fn connect(
+        pool: &AnyPool,
+        connect_options: &ConnectOptions,
+        transactional: bool,
+    ) -> Result<Self> {
+        let options = pool.connect_options();
+        let url = options.database_url.as_str();
+        let mut attempt = 1;
+        let mut conn = loop {
+            let result = tokio::time::timeout(
+                connect_options.connect_timeout(),
+                PgConnection::connect(url),
+            )
+            .await
+            .unwrap_or(Err(sqlx::Error::PoolTimedOut));
+            match result {
+                Ok(conn) => break conn,
+                Err(error) if let Some(delay) = connect_options.retry_delay(attempt, &error) => {
+                    warning!(
+                        "connecting for COPY failed, retrying in {:?}: {}",
+                        delay,
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(source) => {
+                    let mut url = options.database_url.clone();
+                    if url.password().is_some() {
+                        let _ = url.set_password(Some(REDACTED));
+                    }
+                    return Err(Error::ConnectError {
+                        url: url.to_string(),
+                        source,
+                    });
+                }
+            }
+        };
+        if transactional {
+            conn.execute("BEGIN").await?;
+        }
+        Ok(Self {
+            conn,
+            transactional,
+        })
+    }
+
+    pub(crate) async fn execute(&mut self, statement: &str) -> sqlx::Result<()> {
+        self.conn.execute(statement).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn count(&mut self, statement: &str) -> sqlx::Result<i64> {
+        sqlx::query_scalar(statement)
+            .fetch_one(&mut self.conn)
+            .await
+    }
+
+    /// Sends `data` in `COPY` text format with `statement`, returning the
+    /// number of rows stored.
+    pub(crate) async fn copy(&mut self, statement: &str, data: String) -> sqlx::Result<u64> {
+        let mut copy = self.conn.copy_in_raw(statement).await?;
+        match copy.send(data.into_bytes()).await {
+            Ok(_) => copy.finish().await,
+            Err(error) => {
+                copy.abort(error.to_string()).await?;
+                Err(error)
+            }
+        }
+    }
+
+    // Dropped without it, the connection closes and Postgres rolls back.
+    pub(crate) async fn commit(mut self) -> Result<()> {
+        if self.transactional {
+            self.conn.execute("COMMIT").await?;
+        }
+        self.conn.close().await?;
+        Ok(())
+    }
+}
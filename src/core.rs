@@ -1,25 +1,2523 @@
 use crate::{
-    Result,
-    config::{DatabaseConfig, ExcelConfig},
-    utils::read_header,
+    Error, LoadReport, Result, SheetsReport,
+    config::{
+        BoolStrings, ConnectOptions, CreateMode, CsvFormat, Database, DatabaseConfig, ExcelConfig,
+        ExcelConfigBuilder, LoadConfig, OnTypeError, Verify,
+    },
+    csv::{csv_record, csv_value},
+    log::{debug, info, warning},
+    sink::RowSink,
+    sql::{
+        TableName, comment_sql, count_rows_sql, create_index_sql, create_schema_sql,
+        create_table_sql, drop_table_sql, insert_sql, insert_values_sql, max_bind_params,
+        mysql_index_exists_sql, on_conflict_sql, truncate_table_sql,
+    },
+    stream::{RowStream, range_from_rows},
+    utils::{
+        ExcelDataType, TableSchema, WorkbookReader, cell_range, clean_row, coerce, fits_type,
+        is_blank_row, is_null, merged_regions, open_config_workbook, open_workbook,
+        parse_bool_strings, prepare_sheet, read_header, read_schema, read_sheet, select_columns,
+        sheet_range,
+    },
+    value::CellValue,
 };
-use calamine::{Reader, open_workbook_auto};
-use sqlx::query;
+#[cfg(feature = "postgres")]
+use crate::{
+    copy::PgCopy,
+    sql::{copy_row, copy_sql},
+};
+use calamine::{Data, DataType, Range, Reader, Sheets};
+use futures_util::{StreamExt, stream};
+use sqlx::{Any, AnyConnection, AnyPool, Transaction, pool::PoolConnection, query};
+use std::{
+    borrow::Cow,
+    io::Write,
+    path::PathBuf,
+    slice,
+    sync::{Mutex, PoisonError},
+    time::Instant,
+};
+
+pub async fn excel2sql(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+) -> Result<LoadReport> {
+    excel2sql_with_config(excel_config, db_config, &LoadConfig::default()).await
+}
+
+pub async fn excel2sql_with_config(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<LoadReport> {
+    excel2sql_with_progress(excel_config, db_config, load_config, |_, _| {}).await
+}
+
+/// Loads a range the caller read or built itself, e.g. a sheet filtered in
+/// memory, into the table of an [`ExcelConfigBuilder::for_range`] config.
+/// The header and data rows are found in it as in a sheet.
+pub async fn load_range(
+    range: &Range<Data>,
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<LoadReport> {
+    let started = Instant::now();
+    let pool = db_config.connect().await?;
+    let sheet = cell_range(range.clone(), excel_config.range());
+    let sheet = prepare_sheet(sheet, excel_config);
+
+    let mut progress = |_, _| {};
+    load(
+        &pool,
+        excel_config,
+        load_config,
+        db_config.connect_options(),
+        &sheet,
+        &mut progress,
+        started,
+    )
+    .await
+}
+
+/// Like [`excel2sql_with_config`], calling `progress` after every flushed
+/// batch with the number of rows processed so far and the total row count.
+pub async fn excel2sql_with_progress(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+    mut progress: impl FnMut(u64, Option<u64>) + Send,
+) -> Result<LoadReport> {
+    let started = Instant::now();
+    let pool = db_config.connect().await?;
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+
+    load(
+        &pool,
+        excel_config,
+        load_config,
+        db_config.connect_options(),
+        &sheet,
+        &mut progress,
+        started,
+    )
+    .await
+}
+
+/// Like [`excel2sql`], but loads over an existing pool instead of opening a
+/// new one. Failed batches are not retried.
+pub async fn excel2sql_with_pool(excel_config: &ExcelConfig, pool: &AnyPool) -> Result<LoadReport> {
+    excel2sql_with_pool_and_config(excel_config, pool, &LoadConfig::default()).await
+}
+
+pub async fn excel2sql_with_pool_and_config(
+    excel_config: &ExcelConfig,
+    pool: &AnyPool,
+    load_config: &LoadConfig,
+) -> Result<LoadReport> {
+    let started = Instant::now();
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+
+    load(
+        pool,
+        excel_config,
+        load_config,
+        &ConnectOptions::default(),
+        &sheet,
+        &mut |_, _| {},
+        started,
+    )
+    .await
+}
+
+pub async fn excel2sql_all_sheets(
+    path: impl Into<PathBuf>,
+    db_config: &DatabaseConfig,
+) -> Result<Vec<(String, Result<LoadReport>)>> {
+    excel2sql_all_sheets_with_config(path, db_config, &LoadConfig::default()).await
+}
+
+/// Loads every non-empty sheet into a table named after it, running several
+/// sheets at once over one shared pool. A failing sheet doesn't stop the
+/// others; each result is returned next to its sheet name, in workbook order.
+///
+/// The sheets run as futures of the calling task rather than as spawned
+/// tasks: a load mostly waits on the database, which one task overlaps just
+/// as well, and reading a sheet needs the one workbook reader either way.
+/// Each sheet is only read once its turn comes, so at most
+/// [`sheet_concurrency`](crate::config::LoadConfigBuilder::sheet_concurrency)
+/// sheets are held in memory.
+pub async fn excel2sql_all_sheets_with_config(
+    path: impl Into<PathBuf>,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<Vec<(String, Result<LoadReport>)>> {
+    let path = path.into();
+    let pool = db_config.connect().await?;
+    let excel_config = ExcelConfigBuilder::new(path).build()?;
+    let workbook = open_config_workbook(&excel_config)?;
+    let names = workbook.sheet_names();
+    let report = load_sheets(
+        &pool,
+        &excel_config,
+        workbook,
+        &names,
+        db_config,
+        load_config,
+    )
+    .await?;
+    Ok(report.sheets)
+}
+
+/// Like [`excel2sql_all_sheets_with_config`], but loads only `sheets`, in the
+/// given order. A sheet the workbook doesn't have fails the call, unless
+/// [`skip_missing_sheets`](crate::config::LoadConfigBuilder::skip_missing_sheets) leaves it
+/// out and lists it in the report.
+pub async fn excel2sql_sheets_with_config(
+    path: impl Into<PathBuf>,
+    sheets: &[String],
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<SheetsReport> {
+    let pool = db_config.connect().await?;
+    let excel_config = ExcelConfigBuilder::new(path).build()?;
+    let workbook = open_config_workbook(&excel_config)?;
+    load_sheets(
+        &pool,
+        &excel_config,
+        workbook,
+        sheets,
+        db_config,
+        load_config,
+    )
+    .await
+}
+
+// Loads each of `names` with the settings of `excel_config`, into a table
+// named after the sheet.
+async fn load_sheets(
+    pool: &AnyPool,
+    excel_config: &ExcelConfig,
+    workbook: Sheets<WorkbookReader>,
+    names: &[String],
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<SheetsReport> {
+    let (names, skipped_sheets) = present_sheets(&workbook.sheet_names(), names);
+    if !skipped_sheets.is_empty() {
+        if !load_config.skip_missing_sheets() {
+            return Err(Error::ExcelConfigError(format!(
+                "Sheets not in the workbook: {}",
+                skipped_sheets.join(", ")
+            )));
+        }
+        warning!("skipping missing sheets: {}", skipped_sheets.join(", "));
+    }
+
+    let concurrency = match db_config.database() {
+        Database::Sqlite => 1,
+        _ => load_config
+            .sheet_concurrency()
+            .unwrap_or(pool.options().get_max_connections() as usize),
+    };
+    // The lock is only held while a sheet is read, never across an await.
+    let workbook = &Mutex::new(workbook);
+    let results: Vec<_> = stream::iter(names)
+        .map(|name| async move {
+            let started = Instant::now();
+            let excel_config = excel_config.for_sheet(&name);
+            let sheet = {
+                let mut workbook = workbook.lock().unwrap_or_else(PoisonError::into_inner);
+                read_sheet(&mut workbook, &excel_config)
+            };
+            let result = match sheet {
+                Ok(sheet) if sheet.is_empty() => {
+                    warning!("skipping empty sheet `{}`", name);
+                    return None;
+                }
+                Ok(sheet) => {
+                    load(
+                        pool,
+                        &excel_config,
+                        load_config,
+                        db_config.connect_options(),
+                        &sheet,
+                        &mut |_, _| {},
+                        started,
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            Some((name, result))
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+
+    Ok(SheetsReport {
+        sheets: results.into_iter().flatten().collect(),
+        skipped_sheets,
+    })
+}
+
+// Splits `names` into the sheets `workbook` has and those it doesn't.
+fn present_sheets(workbook: &[String], names: &[String]) -> (Vec<String>, Vec<String>) {
+    names
+        .iter()
+        .cloned()
+        .partition(|name| workbook.contains(name))
+}
 
-pub async fn excel2sql(excel_config: &ExcelConfig, db_config: &DatabaseConfig) -> Result<()> {
+pub async fn excel2sql_union(
+    path: impl Into<PathBuf>,
+    sheets: &[String],
+    db_config: &DatabaseConfig,
+) -> Result<LoadReport> {
+    let first = sheets
+        .first()
+        .ok_or_else(|| Error::ExcelConfigError("No sheets to union".to_owned()))?;
+    let excel_config = ExcelConfigBuilder::new(path).sheet(first).build()?;
+    excel2sql_union_with_config(&excel_config, sheets, db_config, &LoadConfig::default()).await
+}
+
+/// Loads `sheets` one after the other into the table of `excel_config`, each
+/// read with its settings. The sheets have to share their columns and types,
+/// a column that is empty in some of them takes its type from the others.
+/// Row numbers in errors are those of the sheet the row is in.
+pub async fn excel2sql_union_with_config(
+    excel_config: &ExcelConfig,
+    sheets: &[String],
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<LoadReport> {
+    let started = Instant::now();
     let pool = db_config.connect().await?;
-    let mut workbook = open_workbook_auto(excel_config.path())?;
-    let sheet = workbook.worksheet_range(excel_config.sheet())?;
-    let (_header, _col_types) = read_header(excel_config, &sheet)?;
+    let (schema, mut parts, skipped) =
+        read_union(excel_config, sheets, load_config.skip_missing_sheets())?;
+    if load_config.schema_only() {
+        parts.clear();
+    }
 
-    let create_cmd = format!(
-        "CREATE TABLE IF NOT EXISTS {} ({} {});",
+    let mut progress = |_, _| {};
+    let mut sink = SqlSink::new(
+        &pool,
+        excel_config,
+        load_config,
+        db_config.connect_options(),
+        &mut progress,
+    )
+    .await?;
+    sink.total = Some(
+        parts
+            .iter()
+            .map(|(config, sheet, _)| data_rows(sheet, config) as u64)
+            .sum(),
+    );
+    sink.begin(&schema).await?;
+    for (config, sheet, selection) in &parts {
+        sink.start_sheet(config.data_start_row()).await?;
+        let mut blank_rows = BlankRows::new(config);
+        for row in sheet.rows().skip(config.data_start_row() - 1) {
+            if !write_row(
+                &mut sink,
+                &mut blank_rows,
+                &select_columns(row, selection.as_deref()),
+            )
+            .await?
+            {
+                break;
+            }
+        }
+    }
+    sink.finish().await?;
+    let mut report = sink.report(started);
+    report.skipped_sheets = skipped;
+    log_report(&report);
+    Ok(report)
+}
+
+type UnionPart = (ExcelConfig, Range<Data>, Option<Vec<usize>>);
+
+// Reads every sheet of a union and the schema they share, listing all the
+// columns that differ from the first sheet if they don't. With `skip_missing`
+// sheets not in the workbook are left out and returned last.
+fn read_union(
+    excel_config: &ExcelConfig,
+    sheets: &[String],
+    skip_missing: bool,
+) -> Result<(TableSchema, Vec<UnionPart>, Vec<String>)> {
+    if sheets.is_empty() {
+        return Err(Error::ExcelConfigError("No sheets to union".to_owned()));
+    }
+    let mut workbook = open_config_workbook(excel_config)?;
+    let names = workbook.sheet_names();
+    let mut schema: Option<TableSchema> = None;
+    let mut parts: Vec<UnionPart> = Vec::with_capacity(sheets.len());
+    let mut skipped = Vec::new();
+    let mut diff = Vec::new();
+    for name in sheets {
+        if skip_missing && !names.contains(name) {
+            warning!("skipping missing sheet `{}`", name);
+            skipped.push(name.clone());
+            continue;
+        }
+        let config = excel_config.with_sheet(name);
+        let sheet = read_sheet(&mut workbook, &config)?;
+        let (sheet_schema, selection) = read_schema(&config, &sheet)?;
+        match schema.as_mut() {
+            None => schema = Some(sheet_schema),
+            Some(schema) => diff.extend(merge_schema(
+                schema,
+                &sheet_schema,
+                parts[0].0.sheet(),
+                name,
+            )),
+        }
+        parts.push((config, sheet, selection));
+    }
+    if parts.is_empty() {
+        return Err(Error::ExcelConfigError(format!(
+            "None of the sheets to union exist: {}",
+            skipped.join(", ")
+        )));
+    }
+    if !diff.is_empty() {
+        return Err(Error::ExcelConfigError(format!(
+            "The sheets do not share one schema:\n{}",
+            diff.join("\n")
+        )));
+    }
+    Ok((schema.unwrap_or_default(), parts, skipped))
+}
+
+// Widens `schema` by the columns of `other`, returning how they differ.
+fn merge_schema(
+    schema: &mut TableSchema,
+    other: &TableSchema,
+    first: &str,
+    sheet: &str,
+) -> Vec<String> {
+    let mut diff = Vec::new();
+    let width = schema.columns.len().max(other.columns.len());
+    for i in 0..width {
+        match (schema.columns.get_mut(i), other.columns.get(i)) {
+            (Some(column), Some(theirs)) if column.name != theirs.name => diff.push(format!(
+                "column {}: `{}` in `{}`, `{}` in `{}`",
+                i + 1,
+                column.name,
+                first,
+                theirs.name,
+                sheet
+            )),
+            (Some(column), Some(theirs)) => {
+                match (column.data_type, theirs.data_type) {
+                    (ours, theirs) if ours == theirs => {}
+                    (_, ExcelDataType::NULL) => {}
+                    (ExcelDataType::NULL, theirs) => column.data_type = theirs,
+                    (ours, theirs) => diff.push(format!(
+                        "column `{}`: {:?} in `{}`, {:?} in `{}`",
+                        column.name, ours, first, theirs, sheet
+                    )),
+                }
+                column.nullable |= theirs.nullable;
+            }
+            (Some(column), None) => diff.push(format!(
+                "column {}: `{}` in `{}`, missing in `{}`",
+                i + 1,
+                column.name,
+                first,
+                sheet
+            )),
+            (None, Some(theirs)) => diff.push(format!(
+                "column {}: missing in `{}`, `{}` in `{}`",
+                i + 1,
+                first,
+                theirs.name,
+                sheet
+            )),
+            (None, None) => unreachable!(),
+        }
+    }
+    diff
+}
+
+/// Writes the sheet as CSV with a header line instead of loading it into a
+/// database, formatting cells by their inferred types. Returns the number of
+/// rows written.
+pub fn excel2csv(excel_config: &ExcelConfig, output: impl Write) -> Result<u64> {
+    excel2csv_with_format(excel_config, &CsvFormat::default(), output)
+}
+
+/// Like [`excel2csv`], with the delimiter, quote and line terminator of
+/// `format`, e.g. semicolons or tabs for tools that expect those.
+pub fn excel2csv_with_format(
+    excel_config: &ExcelConfig,
+    format: &CsvFormat,
+    mut output: impl Write,
+) -> Result<u64> {
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+    let (schema, selection) = read_schema(excel_config, &sheet)?;
+
+    let col_types = schema.data_types();
+
+    output.write_all(csv_record(schema.names(), format).as_bytes())?;
+    let mut written = 0;
+    let mut blank_rows = BlankRows::new(excel_config);
+    for row in sheet.rows().skip(excel_config.data_start_row() - 1) {
+        let row = select_columns(row, selection.as_deref());
+        match blank_rows.next(&row) {
+            RowKind::Data => {}
+            RowKind::Skip => continue,
+            RowKind::End => break,
+        }
+        let row = parse_bool_strings(row, &col_types, excel_config.bool_strings());
+        let fields = schema.columns.iter().enumerate().map(|(i, column)| {
+            csv_value(
+                row.get(i).unwrap_or(&Data::Empty),
+                column.data_type,
+                excel_config.empty_string_as_null(),
+            )
+        });
+        output.write_all(csv_record(fields, format).as_bytes())?;
+        written += 1;
+    }
+    output.flush()?;
+
+    Ok(written)
+}
+
+/// Writes the `CREATE TABLE` statement for the sheet, keys and constraints
+/// included, e.g. to keep it as a migration file. No database is contacted.
+pub fn write_schema(
+    excel_config: &ExcelConfig,
+    database: Database,
+    mut output: impl Write,
+) -> Result<()> {
+    check_schema(excel_config, database)?;
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+    let schema = read_header(excel_config, &sheet)?;
+    if let Some(statement) = excel_config
+        .schema()
+        .and_then(|schema| create_schema_sql(schema, database))
+    {
+        writeln!(output, "{}", statement)?;
+    }
+    let statement = create_table_sql(
+        table_name(excel_config),
+        &schema,
+        database,
+        excel_config.id_column(),
+        excel_config.loaded_at_column(),
+        excel_config.charset(),
+        excel_config.collation(),
+    );
+    writeln!(output, "{}", statement)?;
+    for statement in index_sql(excel_config, &LoadConfig::default(), database, &schema) {
+        writeln!(output, "{}", statement)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// The sheet's columns as JSON, each with its name, its SQL type on
+/// `database`, whether it is nullable, whether it is part of the primary key
+/// and its default. No database is contacted.
+#[cfg(feature = "serde")]
+pub fn schema_json(excel_config: &ExcelConfig, database: Database) -> Result<String> {
+    #[derive(serde::Serialize)]
+    struct Column<'a> {
+        name: &'a str,
+        sql_type: String,
+        nullable: bool,
+        primary_key: bool,
+        default: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comment: Option<&'a str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Table<'a> {
+        table: &'a str,
+        schema: Option<&'a str>,
+        columns: Vec<Column<'a>>,
+    }
+
+    check_schema(excel_config, database)?;
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+    let schema = read_header(excel_config, &sheet)?;
+    let table = Table {
+        table: excel_config.table_name(),
+        schema: excel_config.schema(),
+        columns: schema
+            .columns
+            .iter()
+            .map(|column| Column {
+                name: &column.name,
+                sql_type: crate::sql::column_sql_type(column, database),
+                nullable: column.nullable,
+                primary_key: column.is_primary_key,
+                default: column.default.as_deref(),
+                comment: column.comment.as_deref(),
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&table).map_err(std::io::Error::from)?)
+}
+
+/// Number of data rows the sheet would load, without loading them. Like the
+/// progress total it counts every row from the data start row on, including
+/// empty ones.
+pub fn count_rows(excel_config: &ExcelConfig) -> Result<usize> {
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = sheet_range(
+        &mut workbook,
         excel_config.sheet(),
-        1,
-        1,
+        excel_config.range(),
+        false,
+    )?;
+    Ok(data_rows(&sheet, excel_config))
+}
+
+fn data_rows(sheet: &Range<Data>, excel_config: &ExcelConfig) -> usize {
+    let rows = sheet
+        .height()
+        .saturating_sub(excel_config.data_start_row() - 1);
+    rows.min(excel_config.max_rows().unwrap_or(usize::MAX))
+}
+
+pub fn list_sheets(path: impl Into<PathBuf>) -> Result<Vec<String>> {
+    let workbook = open_workbook(&path.into(), None)?;
+    Ok(workbook.sheet_names())
+}
+
+pub fn excel2sql_preview(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+) -> Result<Vec<String>> {
+    excel2sql_preview_with_config(excel_config, db_config, &LoadConfig::default())
+}
+
+pub fn excel2sql_preview_with_config(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<Vec<String>> {
+    check_schema(excel_config, db_config.database())?;
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+    let (schema, selection) = read_schema(excel_config, &sheet)?;
+    let mut names = schema.names();
+    let mut col_types = schema.data_types();
+
+    let mut statements = schema_sql(excel_config, load_config, db_config.database(), &schema);
+    if load_config.schema_only() {
+        statements.extend(index_sql(
+            excel_config,
+            load_config,
+            db_config.database(),
+            &schema,
+        ));
+        return Ok(statements);
+    }
+    let on_conflict = on_conflict_sql(&schema, excel_config.upsert_key(), db_config.database());
+
+    let loaded_at = excel_config.loaded_at_column().map(|name| {
+        names.push(name.to_owned());
+        loaded_at()
+    });
+    let defaulted = defaulted_columns(&schema);
+    let mut blank_rows = BlankRows::new(excel_config);
+    let mut rows = Vec::new();
+    for row in sheet.rows().skip(excel_config.data_start_row() - 1) {
+        let row = select_columns(row, selection.as_deref());
+        match blank_rows.next(&row) {
+            RowKind::Data => {}
+            RowKind::Skip => continue,
+            RowKind::End => break,
+        }
+        let row = parse_bool_strings(row, &col_types, excel_config.bool_strings());
+        if loaded_at.is_none() {
+            rows.push(row);
+            continue;
+        }
+        let (mut row, _) = fit_row(&row, col_types.len());
+        row.extend(loaded_at.clone());
+        rows.push(Cow::Owned(row));
+    }
+    if loaded_at.is_some() {
+        col_types.push(ExcelDataType::DateTime);
+    }
+    let batches = split_batches(
+        &rows,
+        batch_size(load_config, &col_types, db_config.database()),
+        load_config.max_batch_bytes(),
+        4,
     );
+    let empty_string_as_null = excel_config.empty_string_as_null();
+    for batch in batches {
+        for (rows, omitted) in default_runs(batch, &defaulted, empty_string_as_null) {
+            let rows = rows
+                .iter()
+                .map(|row| kept(row, &omitted))
+                .collect::<Vec<_>>();
+            statements.push(insert_values_sql(
+                table_name(excel_config),
+                &kept(&names, &omitted),
+                &rows,
+                &kept(&col_types, &omitted),
+                db_config.database(),
+                &on_conflict,
+                empty_string_as_null,
+            ));
+        }
+    }
+    statements.extend(index_sql(
+        excel_config,
+        load_config,
+        db_config.database(),
+        &schema,
+    ));
+
+    Ok(statements)
+}
+
+pub async fn excel2sql_streaming(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+) -> Result<LoadReport> {
+    excel2sql_streaming_with_config(excel_config, db_config, &LoadConfig::default()).await
+}
+
+/// Like [`excel2sql_with_config`], but reads the sheet row by row so memory
+/// stays bounded by the batch size and the type inference sample. Only xlsx
+/// and xlsb files can be streamed.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(sheet = excel_config.sheet(), table = excel_config.table_name()))
+)]
+pub async fn excel2sql_streaming_with_config(
+    excel_config: &ExcelConfig,
+    db_config: &DatabaseConfig,
+    load_config: &LoadConfig,
+) -> Result<LoadReport> {
+    let started = Instant::now();
+    let pool = db_config.connect().await?;
+    let mut workbook = open_config_workbook(excel_config)?;
+    let merged = if excel_config.fill_merged_cells() {
+        merged_regions(&mut workbook, excel_config.sheet())?
+    } else {
+        Vec::new()
+    };
+    let mut rows = RowStream::open(
+        &mut workbook,
+        excel_config.sheet(),
+        excel_config.range(),
+        merged,
+    )?;
+
+    // Buffer just enough rows to read the header and infer the column types.
+    let data_offset = excel_config.data_start_row() - 1;
+    let max_rows = excel_config.max_rows().unwrap_or(usize::MAX);
+    let mut sample = Vec::new();
+    while sample.len() < data_offset + excel_config.infer_rows().min(max_rows) {
+        match rows.next_row()? {
+            Some(mut row) => {
+                if sample.len() >= data_offset {
+                    clean_row(&mut row, excel_config);
+                }
+                sample.push(row);
+            }
+            None => break,
+        }
+    }
+    let (mut schema, selection) =
+        read_schema(excel_config, &range_from_rows(&sample, rows.start()))?;
+    // Rows past the sample are unknown, so every column has to stay nullable.
+    for column in &mut schema.columns {
+        column.nullable = true;
+    }
+
+    let mut progress = |_, _| {};
+    let mut sink = SqlSink::new(
+        &pool,
+        excel_config,
+        load_config,
+        db_config.connect_options(),
+        &mut progress,
+    )
+    .await?;
+    sink.begin(&schema).await?;
+    let mut blank_rows = BlankRows::new(excel_config);
+    let mut ended = load_config.schema_only();
+    for row in sample.iter().skip(data_offset) {
+        if ended {
+            break;
+        }
+        ended = !write_row(
+            &mut sink,
+            &mut blank_rows,
+            &select_columns(row, selection.as_deref()),
+        )
+        .await?;
+    }
+    let mut remaining = max_rows - sample.len().saturating_sub(data_offset);
+    while !ended
+        && remaining > 0
+        && let Some(mut row) = rows.next_row()?
+    {
+        remaining -= 1;
+        clean_row(&mut row, excel_config);
+        ended = !write_row(
+            &mut sink,
+            &mut blank_rows,
+            &select_columns(&row, selection.as_deref()),
+        )
+        .await?;
+    }
+    sink.finish().await?;
+    let report = sink.report(started);
+    log_report(&report);
+    Ok(report)
+}
+
+/// Reads the sheet like [`excel2sql`] does, handing the schema and rows to
+/// `sink` instead of a database. Returns the result of [`RowSink::finish`].
+pub async fn excel2sink<S: RowSink>(excel_config: &ExcelConfig, sink: &mut S) -> Result<u64> {
+    let mut workbook = open_config_workbook(excel_config)?;
+    let sheet = read_sheet(&mut workbook, excel_config)?;
+    drive(excel_config, &sheet, sink).await
+}
+
+async fn drive<S: RowSink>(
+    excel_config: &ExcelConfig,
+    sheet: &Range<Data>,
+    sink: &mut S,
+) -> Result<u64> {
+    let (schema, selection) = read_schema(excel_config, sheet)?;
+    sink.begin(&schema).await?;
+    let mut blank_rows = BlankRows::new(excel_config);
+    for row in sheet.rows().skip(excel_config.data_start_row() - 1) {
+        if !write_row(
+            sink,
+            &mut blank_rows,
+            &select_columns(row, selection.as_deref()),
+        )
+        .await?
+        {
+            break;
+        }
+    }
+    sink.finish().await
+}
+
+// Hands `row` to `sink` unless it is a blank row to skip. Returns false once
+// a run of blank rows ends the sheet.
+async fn write_row<S: RowSink>(
+    sink: &mut S,
+    blank_rows: &mut BlankRows<'_>,
+    row: &[Data],
+) -> Result<bool> {
+    match blank_rows.next(row) {
+        RowKind::Data => sink.write_row(row).await?,
+        RowKind::Skip => sink.skip_row().await?,
+        RowKind::End => return Ok(false),
+    }
+    Ok(true)
+}
+
+enum RowKind {
+    Data,
+    Skip,
+    End,
+}
+
+// Follows the runs of blank data rows, which are skipped unless kept and end
+// the sheet once `max_blank_rows` of them follow each other.
+struct BlankRows<'a> {
+    excel_config: &'a ExcelConfig,
+    run: usize,
+}
+
+impl<'a> BlankRows<'a> {
+    fn new(excel_config: &'a ExcelConfig) -> Self {
+        Self {
+            excel_config,
+            run: 0,
+        }
+    }
+
+    fn next(&mut self, row: &[Data]) -> RowKind {
+        if !is_blank_row(row, self.excel_config.empty_string_as_null()) {
+            self.run = 0;
+            return RowKind::Data;
+        }
+        self.run += 1;
+        if self
+            .excel_config
+            .max_blank_rows()
+            .is_some_and(|max| self.run >= max)
+        {
+            RowKind::End
+        } else if self.excel_config.keep_blank_rows() {
+            RowKind::Data
+        } else {
+            RowKind::Skip
+        }
+    }
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(sheet = excel_config.sheet(), table = excel_config.table_name()))
+)]
+async fn load(
+    pool: &AnyPool,
+    excel_config: &ExcelConfig,
+    load_config: &LoadConfig,
+    connect_options: &ConnectOptions,
+    sheet: &Range<Data>,
+    progress: &mut (dyn FnMut(u64, Option<u64>) + Send),
+    started: Instant,
+) -> Result<LoadReport> {
+    let mut sink = SqlSink::new(pool, excel_config, load_config, connect_options, progress).await?;
+    if load_config.schema_only() {
+        let (schema, _) = read_schema(excel_config, sheet)?;
+        sink.total = Some(0);
+        sink.begin(&schema).await?;
+        sink.finish().await?;
+    } else {
+        sink.total = Some(data_rows(sheet, excel_config) as u64);
+        drive(excel_config, sheet, &mut sink).await?;
+    }
+    let report = sink.report(started);
+    log_report(&report);
+    Ok(report)
+}
+
+fn log_report(report: &LoadReport) {
+    if !report.skipped_sheets.is_empty() {
+        warning!(
+            "skipped missing sheets of `{}`: {}",
+            report.table_name,
+            report.skipped_sheets.join(", ")
+        );
+    }
+    if report.cells_set_null > 0 {
+        warning!(
+            "stored {} cells of `{}` as NULL that did not fit their column",
+            report.cells_set_null,
+            report.table_name
+        );
+    }
+    if !report.row_errors.is_empty() {
+        warning!(
+            "skipped {} rows of `{}` that failed to load",
+            report.row_errors.len(),
+            report.table_name
+        );
+    }
+    info!(
+        "loaded {} of {} rows into `{}` in {:?}",
+        report.rows_inserted, report.rows_read, report.table_name, report.elapsed
+    );
+}
+
+enum SqlConnection {
+    Transaction(Transaction<'static, Any>),
+    Connection(PoolConnection<Any>),
+}
+
+impl SqlConnection {
+    fn as_mut(&mut self) -> &mut AnyConnection {
+        match self {
+            SqlConnection::Transaction(tx) => tx,
+            SqlConnection::Connection(conn) => conn,
+        }
+    }
+}
+
+/// Loads rows into the configured table in batches, all inside one
+/// transaction when the load is transactional.
+struct SqlSink<'a> {
+    pool: AnyPool,
+    conn: Option<SqlConnection>,
+    excel_config: &'a ExcelConfig,
+    load_config: &'a LoadConfig,
+    connect_options: &'a ConnectOptions,
+    database: Database,
+    inserter: Option<BatchInserter<'a>>,
+    #[cfg(feature = "postgres")]
+    copy: Option<PgCopy>,
+    #[cfg(feature = "postgres")]
+    copy_cmd: String,
+    batch: Vec<Vec<Data>>,
+    batch_bytes: usize,
+    width: usize,
+    rows_read: u64,
+    rows_truncated: u64,
+    rows_rejected: u64,
+    row_errors: Vec<(usize, String)>,
+    loaded_at: Option<Data>,
+    index_cmds: Vec<String>,
+    // Rows in the table before the load, when it is verified.
+    rows_before: Option<u64>,
+    rows_counted: Option<u64>,
+    total: Option<u64>,
+    progress: &'a mut (dyn FnMut(u64, Option<u64>) + Send),
+}
+
+impl<'a> SqlSink<'a> {
+    async fn new(
+        pool: &AnyPool,
+        excel_config: &'a ExcelConfig,
+        load_config: &'a LoadConfig,
+        connect_options: &'a ConnectOptions,
+        progress: &'a mut (dyn FnMut(u64, Option<u64>) + Send),
+    ) -> Result<Self> {
+        let database = pool_database(pool)?;
+        check_schema(excel_config, database)?;
+        // A COPY load runs everything on a Postgres connection of its own.
+        let conn = if use_copy(excel_config, load_config, database) {
+            None
+        } else if load_config.transactional() {
+            Some(SqlConnection::Transaction(pool.begin().await?))
+        } else {
+            Some(SqlConnection::Connection(pool.acquire().await?))
+        };
+        #[cfg(feature = "postgres")]
+        let copy = match conn {
+            None => {
+                Some(PgCopy::connect(pool, connect_options, load_config.transactional()).await?)
+            }
+            Some(_) => None,
+        };
+        Ok(Self {
+            pool: pool.clone(),
+            conn,
+            excel_config,
+            load_config,
+            connect_options,
+            database,
+            inserter: None,
+            #[cfg(feature = "postgres")]
+            copy,
+            #[cfg(feature = "postgres")]
+            copy_cmd: String::new(),
+            batch: Vec::new(),
+            batch_bytes: 0,
+            width: 0,
+            rows_read: 0,
+            rows_truncated: 0,
+            rows_rejected: 0,
+            row_errors: Vec::new(),
+            loaded_at: excel_config.loaded_at_column().map(|_| loaded_at()),
+            index_cmds: Vec::new(),
+            rows_before: None,
+            rows_counted: None,
+            total: None,
+            progress,
+        })
+    }
+
+    fn report(&self, started: Instant) -> LoadReport {
+        let mut report = LoadReport {
+            table_name: self.excel_config.table_name().to_owned(),
+            rows_read: self.rows_read,
+            rows_rejected: self.rows_rejected,
+            elapsed: started.elapsed(),
+            ..LoadReport::default()
+        };
+        if let Some(inserter) = &self.inserter {
+            report.columns = inserter.names.clone();
+            report.rows_inserted = inserter.inserted;
+            report.rows_skipped = self.rows_read - inserter.processed;
+            report.cells_coerced = inserter.coerced;
+            report.cells_set_null = inserter.nulled;
+            if self.rows_counted.is_some() {
+                report.rows_expected = Some(inserter.processed);
+            }
+        }
+        report.row_errors = self.row_errors.clone();
+        report.rows_counted = self.rows_counted;
+        report
+    }
+
+    fn conn(&mut self) -> &mut AnyConnection {
+        self.conn
+            .as_mut()
+            .expect("SqlSink used after finish")
+            .as_mut()
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if self.inserter.is_none() || self.batch.is_empty() {
+            return Ok(());
+        }
+        let mut batch = std::mem::take(&mut self.batch);
+        let result = self.insert_rows(&batch).await;
+        batch.clear();
+        self.batch = batch;
+        self.batch_bytes = 0;
+        result?;
+        let inserter = self.inserter.as_ref().expect("SqlSink::begin not called");
+        debug!(
+            "inserted rows up to {} into `{}`",
+            inserter.next_row - 1,
+            inserter.table.name
+        );
+        (self.progress)(inserter.processed, self.total);
+        Ok(())
+    }
 
-    query(&create_cmd).execute(&pool).await?;
+    // Inserts `batch` a run at a time, each run leaving out the defaulted
+    // columns its rows are empty in.
+    async fn insert_rows(&mut self, batch: &[Vec<Data>]) -> Result<()> {
+        let inserter = self.inserter.as_mut().expect("SqlSink::begin not called");
+        let runs = default_runs(batch, &inserter.defaulted, inserter.empty_string_as_null);
+        for (rows, omitted) in runs {
+            #[cfg(feature = "postgres")]
+            if let Some(copy) = self.copy.as_mut() {
+                inserter.copy(copy, &self.copy_cmd, rows, &omitted).await?;
+                continue;
+            }
+            let conn = self.conn.as_mut().expect("SqlSink used after finish");
+            let collect_errors = self.load_config.collect_errors();
+            // A failed statement aborts a Postgres transaction unless it is
+            // rolled back to a savepoint.
+            let savepoint = collect_errors && matches!(conn, SqlConnection::Transaction(_));
+            let mut attempt = 1;
+            loop {
+                match insert_batch(inserter, conn.as_mut(), rows, &omitted, savepoint).await {
+                    // A transaction dies with its connection, so only batches
+                    // inserted on their own can be retried.
+                    Err(Error::InsertError { source, .. })
+                        if matches!(conn, SqlConnection::Connection(_))
+                            && let Some(delay) =
+                                self.connect_options.retry_delay(attempt, &source) =>
+                    {
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                        *conn = SqlConnection::Connection(self.pool.acquire().await?);
+                    }
+                    // Find the rejected rows by inserting the batch row by row.
+                    Err(Error::InsertError {
+                        source: sqlx::Error::Database(_),
+                        ..
+                    }) if collect_errors => {
+                        for row in rows {
+                            let row_number = inserter.next_row;
+                            let result = insert_batch(
+                                inserter,
+                                conn.as_mut(),
+                                slice::from_ref(row),
+                                &omitted,
+                                savepoint,
+                            )
+                            .await;
+                            match result {
+                                Err(Error::InsertError {
+                                    source: source @ sqlx::Error::Database(_),
+                                    ..
+                                }) => {
+                                    self.row_errors.push((row_number, source.to_string()));
+                                    inserter.next_row += 1;
+                                }
+                                result => result?,
+                            }
+                        }
+                        break;
+                    }
+                    result => break result?,
+                }
+            }
+        }
+        Ok(())
+    }
 
+    // Runs `statement` on the connection of a COPY load, or else on the
+    // sink's own.
+    async fn execute(&mut self, statement: &str) -> Result<()> {
+        #[cfg(feature = "postgres")]
+        if let Some(copy) = self.copy.as_mut() {
+            return Ok(copy.execute(statement).await?);
+        }
+        query(statement).execute(&mut *self.conn()).await?;
+        Ok(())
+    }
+
+    // Whether a MySQL table loaded before already has the index on `columns`.
+    async fn index_exists(&mut self, columns: &[String]) -> Result<bool> {
+        let statement = mysql_index_exists_sql(table_name(self.excel_config), columns);
+        let count: i64 = sqlx::query_scalar(&statement)
+            .fetch_one(&mut *self.conn())
+            .await?;
+        Ok(count > 0)
+    }
+
+    async fn count_rows(&mut self) -> Result<u64> {
+        let statement = count_rows_sql(table_name(self.excel_config), self.database);
+        #[cfg(feature = "postgres")]
+        if let Some(copy) = self.copy.as_mut() {
+            return Ok(copy.count(&statement).await? as u64);
+        }
+        let count: i64 = sqlx::query_scalar(&statement)
+            .fetch_one(&mut *self.conn())
+            .await?;
+        Ok(count as u64)
+    }
+
+    // Starts counting rows over for the next sheet of a union.
+    async fn start_sheet(&mut self, data_start_row: usize) -> Result<()> {
+        self.flush().await?;
+        if let Some(inserter) = self.inserter.as_mut() {
+            inserter.next_row = data_start_row;
+        }
+        Ok(())
+    }
+}
+
+impl RowSink for SqlSink<'_> {
+    async fn begin(&mut self, schema: &TableSchema) -> Result<()> {
+        for statement in schema_sql(self.excel_config, self.load_config, self.database, schema) {
+            self.execute(&statement).await?;
+        }
+        self.index_cmds = index_sql(self.excel_config, self.load_config, self.database, schema);
+        if self.load_config.verify() != Verify::Off && self.excel_config.upsert_key().is_empty() {
+            self.rows_before = Some(match self.load_config.create_mode() {
+                CreateMode::DropAndCreate | CreateMode::TruncateExisting => 0,
+                CreateMode::IfNotExists | CreateMode::Append => self.count_rows().await?,
+            });
+        }
+        let inserter =
+            BatchInserter::new(self.excel_config, self.load_config, self.database, schema);
+        #[cfg(feature = "postgres")]
+        if self.copy.is_some() {
+            self.copy_cmd = copy_sql(inserter.table, &inserter.names);
+        }
+        self.batch = Vec::with_capacity(inserter.batch_size);
+        self.width = schema.columns.len();
+        self.inserter = Some(inserter);
+        Ok(())
+    }
+
+    async fn write_row(&mut self, row: &[Data]) -> Result<()> {
+        let inserter = self.inserter.as_mut().expect("SqlSink::begin not called");
+        self.rows_read += 1;
+        let (mut row, truncated) = fit_row(row, self.width);
+        self.rows_truncated += truncated as u64;
+        row.extend(self.loaded_at.clone());
+        let row_number = inserter.next_row + self.batch.len();
+        let result = inserter.check_row(&mut row, row_number).and_then(|()| {
+            match self.load_config.row_check() {
+                Some(check) => {
+                    check
+                        .check(&row[..self.width])
+                        .map_err(|reason| Error::RowRejected {
+                            row: row_number,
+                            reason,
+                        })
+                }
+                None => Ok(()),
+            }
+        });
+        if let Err(Error::RowRejected { .. }) = result {
+            self.rows_rejected += 1;
+        }
+        match result {
+            Err(error @ (Error::CellTypeError { .. } | Error::RowRejected { .. }))
+                if self.load_config.collect_errors() =>
+            {
+                // Flush first so the batch stays a run of consecutive rows.
+                self.flush().await?;
+                self.row_errors.push((row_number, error.to_string()));
+                if let Some(inserter) = self.inserter.as_mut() {
+                    inserter.next_row += 1;
+                }
+                return Ok(());
+            }
+            result => result?,
+        }
+        let batch_size = inserter.batch_size;
+        let bytes = inserter.row_sql_bytes + row_bytes(&row);
+        if let Some(max_bytes) = self.load_config.max_batch_bytes()
+            && !self.batch.is_empty()
+            && self.batch_bytes + bytes > max_bytes
+        {
+            self.flush().await?;
+        }
+        self.batch.push(row);
+        self.batch_bytes += bytes;
+        if self.batch.len() >= batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn skip_row(&mut self) -> Result<()> {
+        // Flush first so the batch stays a run of consecutive rows.
+        self.flush().await?;
+        if let Some(inserter) = self.inserter.as_mut() {
+            inserter.next_row += 1;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<u64> {
+        self.flush().await?;
+        if self.rows_truncated > 0 {
+            warning!(
+                "ignored cells past the {} columns of `{}` in {} rows",
+                self.width,
+                self.excel_config.table_name(),
+                self.rows_truncated
+            );
+        }
+        // Indexing once the rows are in is cheaper than updating it per batch.
+        let index_cmds = std::mem::take(&mut self.index_cmds);
+        for (columns, statement) in self.excel_config.indexes().iter().zip(index_cmds) {
+            if self.database == Database::MySQL && self.index_exists(columns).await? {
+                continue;
+            }
+            self.execute(&statement).await?;
+        }
+        if let Some(rows_before) = self.rows_before {
+            let counted = self.count_rows().await?.saturating_sub(rows_before);
+            self.rows_counted = Some(counted);
+            let expected = self.inserter.as_ref().map_or(0, |i| i.processed);
+            if counted != expected {
+                warning!(
+                    "expected {} new rows in `{}`, counted {}",
+                    expected,
+                    self.excel_config.table_name(),
+                    counted
+                );
+                if self.load_config.verify() == Verify::Strict {
+                    return Err(Error::RowCountMismatch {
+                        table: self.excel_config.table_name().to_owned(),
+                        expected,
+                        counted,
+                    });
+                }
+            }
+        }
+        if let Some(SqlConnection::Transaction(tx)) = self.conn.take() {
+            tx.commit().await?;
+        }
+        #[cfg(feature = "postgres")]
+        if let Some(copy) = self.copy.take() {
+            copy.commit().await?;
+        }
+        Ok(self.inserter.as_ref().map_or(0, |i| i.inserted))
+    }
+}
+
+fn schema_sql(
+    excel_config: &ExcelConfig,
+    load_config: &LoadConfig,
+    database: Database,
+    schema: &TableSchema,
+) -> Vec<String> {
+    let table = table_name(excel_config);
+    let create_cmd = || {
+        create_table_sql(
+            table,
+            schema,
+            database,
+            excel_config.id_column(),
+            excel_config.loaded_at_column(),
+            excel_config.charset(),
+            excel_config.collation(),
+        )
+    };
+
+    let mut statements = match load_config.create_mode() {
+        CreateMode::IfNotExists => vec![create_cmd()],
+        CreateMode::DropAndCreate => vec![drop_table_sql(table, database), create_cmd()],
+        CreateMode::TruncateExisting => vec![create_cmd(), truncate_table_sql(table, database)],
+        CreateMode::Append => return Vec::new(),
+    };
+    statements.extend(comment_sql(table, schema, database));
+    if let Some(statement) = excel_config
+        .schema()
+        .and_then(|schema| create_schema_sql(schema, database))
+    {
+        statements.insert(0, statement);
+    }
+    // Off by default in SQLite scripts, sqlx's connections already have it
+    // on and inside a transaction it does nothing.
+    if database == Database::Sqlite && schema.columns.iter().any(|c| c.references.is_some()) {
+        statements.insert(0, "PRAGMA foreign_keys = ON;".to_owned());
+    }
+    statements
+}
+
+// An appended table keeps the indexes it was created with.
+fn index_sql(
+    excel_config: &ExcelConfig,
+    load_config: &LoadConfig,
+    database: Database,
+    schema: &TableSchema,
+) -> Vec<String> {
+    if load_config.create_mode() == CreateMode::Append {
+        return Vec::new();
+    }
+    excel_config
+        .indexes()
+        .iter()
+        .map(|columns| create_index_sql(table_name(excel_config), columns, schema, database))
+        .collect()
+}
+
+/// The current UTC time as a cell for the loaded at column.
+fn loaded_at() -> Data {
+    Data::DateTimeIso(
+        chrono::Utc::now()
+            .format("%Y-%m-%dT%H:%M:%S%.6f")
+            .to_string(),
+    )
+}
+
+fn table_name(excel_config: &ExcelConfig) -> TableName<'_> {
+    TableName {
+        schema: excel_config.schema(),
+        name: excel_config.table_name(),
+    }
+}
+
+// Whether rows go in with COPY, which can neither upsert nor tell which row
+// of a batch failed.
+fn use_copy(excel_config: &ExcelConfig, load_config: &LoadConfig, database: Database) -> bool {
+    cfg!(feature = "postgres")
+        && load_config.copy()
+        && database == Database::Postgres
+        && excel_config.upsert_key().is_empty()
+        && !load_config.collect_errors()
+}
+
+fn pool_database(pool: &AnyPool) -> Result<Database> {
+    Database::from_scheme(pool.connect_options().database_url.scheme())
+}
+
+fn check_schema(excel_config: &ExcelConfig, database: Database) -> Result<()> {
+    if excel_config.schema().is_some() && database == Database::Sqlite {
+        return Err(Error::ExcelConfigError(
+            "A schema cannot be set for SQLite".to_owned(),
+        ));
+    }
     Ok(())
 }
+
+// Keep each statement below the bind parameter limit for wide sheets.
+fn batch_size(load_config: &LoadConfig, col_types: &[ExcelDataType], database: Database) -> usize {
+    load_config
+        .batch_size()
+        .min(max_bind_params(database) / col_types.len().max(1))
+        .max(1)
+}
+
+// Rough size of the values of `row` in a statement, quoted and separated.
+fn row_bytes(row: &[Data]) -> usize {
+    row.iter()
+        .map(|cell| match cell {
+            Data::String(s) => s.len() + 4,
+            Data::Empty => 6,
+            _ => 28,
+        })
+        .sum()
+}
+
+// Cuts `rows` into batches of at most `batch_size` rows and, if set, about
+// `max_bytes` bytes, where a row takes `overhead` bytes besides its values.
+fn split_batches<R: AsRef<[Data]>>(
+    rows: &[R],
+    batch_size: usize,
+    max_bytes: Option<usize>,
+    overhead: usize,
+) -> Vec<&[R]> {
+    let mut batches = Vec::new();
+    let (mut start, mut bytes) = (0, 0);
+    for (i, row) in rows.iter().enumerate() {
+        let row_bytes = overhead + row_bytes(row.as_ref());
+        if i > start
+            && (i - start == batch_size || max_bytes.is_some_and(|max| bytes + row_bytes > max))
+        {
+            batches.push(&rows[start..i]);
+            (start, bytes) = (i, 0);
+        }
+        bytes += row_bytes;
+    }
+    if start < rows.len() {
+        batches.push(&rows[start..]);
+    }
+    batches
+}
+
+struct BatchInserter<'a> {
+    table: TableName<'a>,
+    database: Database,
+    names: Vec<String>,
+    col_types: Vec<ExcelDataType>,
+    // Columns with a `DEFAULT`, left out of the INSERT of their empty cells.
+    defaulted: Vec<usize>,
+    batch_size: usize,
+    // Share of the full batch statement text taken by each row.
+    row_sql_bytes: usize,
+    insert_cmd: String,
+    // Single row statement for a batch size of 1 and the rows retried one
+    // by one, built once instead of per row.
+    row_cmd: String,
+    on_conflict: String,
+    empty_string_as_null: bool,
+    on_type_error: OnTypeError,
+    bool_strings: &'a BoolStrings,
+    next_row: usize,
+    processed: u64,
+    inserted: u64,
+    coerced: u64,
+    nulled: u64,
+}
+
+impl<'a> BatchInserter<'a> {
+    fn new(
+        excel_config: &'a ExcelConfig,
+        load_config: &LoadConfig,
+        database: Database,
+        schema: &TableSchema,
+    ) -> Self {
+        let table = table_name(excel_config);
+        let mut names = schema.names();
+        let mut col_types = schema.data_types();
+        if let Some(name) = excel_config.loaded_at_column() {
+            names.push(name.to_owned());
+            col_types.push(ExcelDataType::DateTime);
+        }
+        let batch_size = batch_size(load_config, &col_types, database);
+        let on_conflict = on_conflict_sql(schema, excel_config.upsert_key(), database);
+        let insert_cmd = insert_sql(
+            table,
+            &names,
+            &col_types,
+            database,
+            batch_size,
+            &on_conflict,
+        );
+        Self {
+            table,
+            database,
+            batch_size,
+            row_sql_bytes: insert_cmd.len() / batch_size,
+            insert_cmd,
+            row_cmd: insert_sql(table, &names, &col_types, database, 1, &on_conflict),
+            names,
+            col_types,
+            defaulted: defaulted_columns(schema),
+            on_conflict,
+            empty_string_as_null: excel_config.empty_string_as_null(),
+            on_type_error: load_config.on_type_error(),
+            bool_strings: excel_config.bool_strings(),
+            next_row: excel_config.data_start_row(),
+            processed: 0,
+            inserted: 0,
+            coerced: 0,
+            nulled: 0,
+        }
+    }
+
+    // Applies the type error policy to the cells of data row `row_number`.
+    fn check_row(&mut self, row: &mut [Data], row_number: usize) -> Result<()> {
+        for ((cell, data_type), name) in row.iter_mut().zip(&self.col_types).zip(&self.names) {
+            if is_null(cell, self.empty_string_as_null) {
+                continue;
+            }
+            if *data_type == ExcelDataType::Bool
+                && let Some(value) = cell.get_string().and_then(|s| self.bool_strings.parse(s))
+            {
+                *cell = Data::Bool(value);
+                continue;
+            }
+            if fits_type(cell, *data_type) {
+                continue;
+            }
+            match self.on_type_error {
+                OnTypeError::Abort => {
+                    return Err(Error::CellTypeError {
+                        row: row_number,
+                        column: name.clone(),
+                        value: cell.to_string(),
+                        data_type: *data_type,
+                    });
+                }
+                OnTypeError::Coerce if let Some(value) = coerce(cell, *data_type) => {
+                    *cell = value;
+                    self.coerced += 1;
+                }
+                OnTypeError::SetNull | OnTypeError::Coerce => {
+                    *cell = Data::Empty;
+                    self.nulled += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Inserts `rows`, leaving out the columns at `omitted`.
+    async fn insert<R: AsRef<[Data]>>(
+        &mut self,
+        conn: &mut AnyConnection,
+        rows: &[R],
+        omitted: &[usize],
+    ) -> Result<()> {
+        let partial_cmd;
+        let insert_cmd = match rows.len() {
+            len if !omitted.is_empty() => {
+                partial_cmd = insert_sql(
+                    self.table,
+                    &kept(&self.names, omitted),
+                    &kept(&self.col_types, omitted),
+                    self.database,
+                    len,
+                    &self.on_conflict,
+                );
+                &partial_cmd
+            }
+            len if len == self.batch_size => &self.insert_cmd,
+            1 => &self.row_cmd,
+            len => {
+                partial_cmd = insert_sql(
+                    self.table,
+                    &self.names,
+                    &self.col_types,
+                    self.database,
+                    len,
+                    &self.on_conflict,
+                );
+                &partial_cmd
+            }
+        };
+
+        // sqlx caches the prepared statement of each connection by its text,
+        // so rows inserted one by one only build the single row statement
+        // once and are then just bound.
+        let insert = rows
+            .iter()
+            .flat_map(|row| {
+                row.as_ref()
+                    .iter()
+                    .zip(&self.col_types)
+                    .enumerate()
+                    .filter(|(i, _)| !omitted.contains(i))
+                    .map(|(_, cell)| cell)
+            })
+            .fold(query(insert_cmd), |insert, (cell, data_type)| {
+                insert.bind(CellValue::from_data(
+                    cell,
+                    *data_type,
+                    self.empty_string_as_null,
+                ))
+            });
+
+        let start = self.next_row;
+        let result = insert
+            .execute(conn)
+            .await
+            .map_err(|source| Error::InsertError {
+                start,
+                end: start + rows.len() - 1,
+                source,
+            })?;
+
+        self.next_row += rows.len();
+        self.processed += rows.len() as u64;
+        self.inserted += result.rows_affected();
+        Ok(())
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn copy(
+        &mut self,
+        copy: &mut PgCopy,
+        statement: &str,
+        rows: &[Vec<Data>],
+        omitted: &[usize],
+    ) -> Result<()> {
+        let mut data = String::new();
+        let col_types = kept(&self.col_types, omitted);
+        for row in rows {
+            let row = match omitted {
+                [] => Cow::Borrowed(row),
+                _ => Cow::Owned(kept(row, omitted)),
+            };
+            copy_row(&mut data, &row, &col_types, self.empty_string_as_null);
+        }
+        let partial_cmd;
+        let statement = if omitted.is_empty() {
+            statement
+        } else {
+            partial_cmd = copy_sql(self.table, &kept(&self.names, omitted));
+            &partial_cmd
+        };
+        let start = self.next_row;
+        let copied = copy
+            .copy(statement, data)
+            .await
+            .map_err(|source| Error::InsertError {
+                start,
+                end: start + rows.len() - 1,
+                source,
+            })?;
+
+        self.next_row += rows.len();
+        self.processed += rows.len() as u64;
+        self.inserted += copied;
+        Ok(())
+    }
+}
+
+// Inserts `rows` in a savepoint if asked, rolling back to it on failure.
+async fn insert_batch(
+    inserter: &mut BatchInserter<'_>,
+    conn: &mut AnyConnection,
+    rows: &[Vec<Data>],
+    omitted: &[usize],
+    savepoint: bool,
+) -> Result<()> {
+    if !savepoint {
+        return inserter.insert(conn, rows, omitted).await;
+    }
+    query("SAVEPOINT excel2sql_batch")
+        .execute(&mut *conn)
+        .await?;
+    let result = inserter.insert(&mut *conn, rows, omitted).await;
+    let end = match result {
+        Ok(()) => "RELEASE SAVEPOINT excel2sql_batch",
+        Err(_) => "ROLLBACK TO SAVEPOINT excel2sql_batch",
+    };
+    query(end).execute(&mut *conn).await?;
+    result
+}
+
+// Positions of the columns with a `DEFAULT`.
+fn defaulted_columns(schema: &TableSchema) -> Vec<usize> {
+    (0..schema.columns.len())
+        .filter(|&i| schema.columns[i].default.is_some())
+        .collect()
+}
+
+// Splits `rows` into runs of consecutive rows that are empty in the same
+// `defaulted` columns, each with those columns. Their INSERT leaves them out
+// so the database stores the column's `DEFAULT`.
+fn default_runs<'r, R: AsRef<[Data]>>(
+    rows: &'r [R],
+    defaulted: &[usize],
+    empty_string_as_null: bool,
+) -> Vec<(&'r [R], Vec<usize>)> {
+    let omitted = rows
+        .iter()
+        .map(|row| {
+            defaulted
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    row.as_ref()
+                        .get(i)
+                        .is_none_or(|cell| is_null(cell, empty_string_as_null))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in 1..=rows.len() {
+        if end == rows.len() || omitted[end] != omitted[start] {
+            runs.push((&rows[start..end], omitted[start].clone()));
+            start = end;
+        }
+    }
+    runs
+}
+
+// `values` without the positions in `omitted`.
+fn kept<T: Clone>(values: &[T], omitted: &[usize]) -> Vec<T> {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !omitted.contains(i))
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+/// Pads `row` with empty cells or cuts it to `width`, telling whether any
+/// non-empty cell was cut off.
+fn fit_row(row: &[Data], width: usize) -> (Vec<Data>, bool) {
+    let truncated = row.iter().skip(width).any(|cell| !cell.is_empty());
+    let mut row = row[..row.len().min(width)].to_vec();
+    row.resize(width, Data::Empty);
+    (row, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_fit_row() {
+        let row = [Data::Int(1), Data::Empty, Data::Int(3)];
+        assert_eq!(
+            fit_row(&row[..1], 3),
+            (vec![Data::Int(1), Data::Empty, Data::Empty], false)
+        );
+        assert_eq!(fit_row(&row[..2], 1), (vec![Data::Int(1)], false));
+        assert_eq!(fit_row(&row, 2), (vec![Data::Int(1), Data::Empty], true));
+    }
+
+    #[tokio::test]
+    async fn test_connect_before_reading() {
+        let dir = std::env::temp_dir().join("excel2sql-no-such-dir");
+        let db_config =
+            DatabaseConfig::new(&format!("sqlite://{}/test.db", dir.display())).unwrap();
+        assert!(matches!(
+            db_config.connect().await,
+            Err(Error::ConnectError { .. })
+        ));
+
+        // The sheet does not exist either, but connecting fails first.
+        let excel_config = ExcelConfigBuilder::new("tests/fixtures/simple.xlsx")
+            .sheet("Missing")
+            .build()
+            .unwrap();
+        let error = excel2sql(&excel_config, &db_config).await.unwrap_err();
+        assert!(matches!(error, Error::ConnectError { .. }), "{}", error);
+        assert!(error.to_string().starts_with("Cannot connect to sqlite://"));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_schema_json() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.ods");
+        let config = ExcelConfigBuilder::new(path)
+            .primary_key(vec!["name".to_owned()])
+            .build()
+            .unwrap();
+        let json = schema_json(&config, Database::MySQL).unwrap();
+        assert_eq!(
+            json.split_whitespace().collect::<String>(),
+            concat!(
+                r#"{"table":"Sheet1","schema":null,"columns":["#,
+                r#"{"name":"id","sql_type":"BIGINT","nullable":false,"primary_key":false,"default":null},"#,
+                r#"{"name":"name","sql_type":"VARCHAR(255)","nullable":false,"primary_key":true,"default":null}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_table_name() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let schema = |config: &ExcelConfig, database| {
+            let mut output = Vec::new();
+            write_schema(config, database, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let config = ExcelConfigBuilder::new(path)
+            .sheet("Other")
+            .build()
+            .unwrap();
+        assert_eq!(config.table_name(), "Other");
+        assert!(
+            schema(&config, Database::Postgres)
+                .starts_with("CREATE TABLE IF NOT EXISTS \"Other\" (")
+        );
+
+        // A name that is no plain identifier is quoted.
+        let config = ExcelConfigBuilder::new(path)
+            .sheet("Other")
+            .table_name("2024 Q1 `Data`")
+            .build()
+            .unwrap();
+        assert_eq!(config.sheet(), "Other");
+        assert!(
+            schema(&config, Database::Postgres)
+                .starts_with("CREATE TABLE IF NOT EXISTS \"2024 Q1 `Data`\" (\"a\" BIGINT")
+        );
+        assert!(
+            schema(&config, Database::MySQL)
+                .starts_with("CREATE TABLE IF NOT EXISTS `2024 Q1 ``Data``` (`a` BIGINT")
+        );
+    }
+
+    #[test]
+    fn test_write_schema() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.ods");
+        let config = ExcelConfigBuilder::new(path)
+            .primary_key(vec!["id".to_owned()])
+            .build()
+            .unwrap();
+        let mut output = Vec::new();
+        write_schema(&config, Database::Postgres, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "CREATE TABLE IF NOT EXISTS \"Sheet1\" (\"id\" BIGINT NOT NULL, \"name\" TEXT NOT NULL, PRIMARY KEY (\"id\"));\n"
+        );
+
+        let config = ExcelConfigBuilder::new(path)
+            .schema("staging")
+            .build()
+            .unwrap();
+        let mut output = Vec::new();
+        write_schema(&config, Database::Postgres, &mut output).unwrap();
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .starts_with("CREATE SCHEMA IF NOT EXISTS \"staging\";\nCREATE TABLE IF NOT EXISTS \"staging\".\"Sheet1\"")
+        );
+        assert!(matches!(
+            write_schema(&config, Database::Sqlite, Vec::new()),
+            Err(Error::ExcelConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_preview_loaded_at_column() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.ods");
+        let config = ExcelConfigBuilder::new(path)
+            .add_loaded_at_column(true)
+            .build()
+            .unwrap();
+        let db_config = DatabaseConfig::new("sqlite::memory:").unwrap();
+        let statements = excel2sql_preview(&config, &db_config).unwrap();
+        assert!(statements[0].ends_with(", \"loaded_at\" TIMESTAMP NOT NULL);"));
+        assert!(statements[1].starts_with(
+            "INSERT INTO \"Sheet1\" (\"id\", \"name\", \"loaded_at\") VALUES (1, 'alice', '"
+        ));
+    }
+
+    #[test]
+    fn test_preview_schema_only() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = ExcelConfigBuilder::new(path)
+            .index(vec!["name".to_owned()])
+            .build()
+            .unwrap();
+        let db_config = DatabaseConfig::new("sqlite::memory:").unwrap();
+        let load_config = crate::config::LoadConfigBuilder::new()
+            .create_mode(CreateMode::DropAndCreate)
+            .schema_only(true)
+            .build()
+            .unwrap();
+        let statements = excel2sql_preview_with_config(&config, &db_config, &load_config).unwrap();
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].starts_with("DROP TABLE"));
+        assert!(statements[1].starts_with("CREATE TABLE"));
+        assert!(statements[2].starts_with("CREATE INDEX"));
+    }
+
+    #[test]
+    fn test_preview_column_defaults() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = ExcelConfigBuilder::new(path)
+            .column_defaults(HashMap::from([("score".to_owned(), "0".to_owned())]))
+            .build()
+            .unwrap();
+        let db_config = DatabaseConfig::new("sqlite::memory:").unwrap();
+        let statements = excel2sql_preview(&config, &db_config).unwrap();
+        assert!(statements[0].contains("\"score\" REAL DEFAULT 0,"));
+        // Bob's empty score is left to the column's default.
+        assert_eq!(statements.len(), 4);
+        assert!(statements[1].contains("\"score\", \"active\""));
+        assert!(statements[2].starts_with(
+            "INSERT INTO \"Sheet1\" (\"id\", \"name\", \"active\", \"when\") VALUES (2, 'bob', FALSE, "
+        ));
+        assert!(statements[3].contains("(3, 'c\"ar''ol', 3.25, TRUE, NULL)"));
+
+        let config = ExcelConfigBuilder::new(path)
+            .column_defaults(HashMap::from([("score".to_owned(), "abc".to_owned())]))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            excel2sql_preview(&config, &db_config),
+            Err(Error::ExcelConfigError(message)) if message == "Invalid default `abc` for the Float column `score`"
+        ));
+        assert!(
+            ExcelConfigBuilder::new(path)
+                .column_defaults(HashMap::from([("status".to_owned(), "x".to_owned())]))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_count_rows() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.ods");
+        let config = ExcelConfigBuilder::new(path).build().unwrap();
+        assert_eq!(count_rows(&config).unwrap(), 2);
+        let config = ExcelConfigBuilder::new(path)
+            .range("A1:B2")
+            .build()
+            .unwrap();
+        assert_eq!(count_rows(&config).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_max_rows() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = ExcelConfigBuilder::new(path).max_rows(2).build().unwrap();
+        assert_eq!(count_rows(&config).unwrap(), 2);
+        let mut output = Vec::new();
+        assert_eq!(excel2csv(&config, &mut output).unwrap(), 2);
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .ends_with("2,bob,,false,2023-03-16T00:00:00\r\n")
+        );
+        let mut output = Vec::new();
+        let format = CsvFormat::new(';', '"', "\n").unwrap();
+        assert_eq!(
+            excel2csv_with_format(&config, &format, &mut output).unwrap(),
+            2
+        );
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .ends_with("2;bob;;false;2023-03-16T00:00:00\n")
+        );
+
+        let config = ExcelConfigBuilder::new(path).max_rows(10).build().unwrap();
+        assert_eq!(count_rows(&config).unwrap(), 3);
+
+        // Without a header the data starts on the first row of the sheet.
+        let config = ExcelConfigBuilder::new(path)
+            .has_header(false)
+            .max_rows(2)
+            .build()
+            .unwrap();
+        assert_eq!(count_rows(&config).unwrap(), 2);
+        let mut output = Vec::new();
+        assert_eq!(excel2csv(&config, &mut output).unwrap(), 2);
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 3);
+        assert!(output.starts_with("col1,col2,col3,col4,col5\r\nid,name,"));
+        assert!(ExcelConfigBuilder::new(path).max_rows(0).build().is_err());
+    }
+
+    #[test]
+    fn test_csv_format() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/csv_format.xlsx"
+        );
+        let config = ExcelConfigBuilder::new(path).build().unwrap();
+
+        let mut output = Vec::new();
+        assert_eq!(excel2csv(&config, &mut output).unwrap(), 3);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "id,name,note\r\n\
+             1,Smith; John,\"say \"\"hi\"\"\"\r\n\
+             2,\"two\nlines\",\r\n\
+             3,plain,it's\r\n"
+        );
+
+        let format = CsvFormat::new(';', '\'', "\n").unwrap();
+        let mut output = Vec::new();
+        assert_eq!(
+            excel2csv_with_format(&config, &format, &mut output).unwrap(),
+            3
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "id;name;note\n\
+             1;'Smith; John';say \"hi\"\n\
+             2;'two\nlines';\n\
+             3;plain;'it''s'\n"
+        );
+
+        let format = CsvFormat::new('\t', '"', "|").unwrap();
+        let mut output = Vec::new();
+        excel2csv_with_format(&config, &format, &mut output).unwrap();
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .starts_with("id\tname\tnote|1\t")
+        );
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        for name in ["simple.xlsx", "simple.xlsb", "simple.xls", "simple.ods"] {
+            let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+            let config = ExcelConfigBuilder::new(&path).build().unwrap();
+            let mut expected = Vec::new();
+            excel2csv(&config, &mut expected).unwrap();
+
+            let bytes = std::fs::read(&path).unwrap();
+            let config = ExcelConfigBuilder::from_bytes(bytes).build().unwrap();
+            let mut output = Vec::new();
+            excel2csv(&config, &mut output).unwrap();
+            assert_eq!(output, expected);
+        }
+
+        assert!(matches!(
+            ExcelConfigBuilder::from_bytes(b"id,name\n".to_vec()).build(),
+            Err(Error::ExcelConfigError(message)) if message.contains("not an xlsx")
+        ));
+    }
+
+    #[test]
+    fn test_formats() {
+        let read = |name: &str| {
+            let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+            let config = ExcelConfigBuilder::new(path).build().unwrap();
+            let mut output = Vec::new();
+            write_schema(&config, Database::Postgres, &mut output).unwrap();
+            excel2csv(&config, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let expected = read("simple.xlsx");
+        assert!(expected.contains("\"id\" BIGINT NOT NULL"));
+        assert!(expected.contains("1,alice,1.5,true,2023-03-15T12:00:00"));
+        assert!(expected.contains("2,bob,,false,2023-03-16T00:00:00"));
+        for name in ["simple.xlsb", "simple.xls"] {
+            assert_eq!(read(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_batch_size() {
+        let load_config = LoadConfig::default();
+        let types = |n| vec![ExcelDataType::Int; n];
+        assert_eq!(batch_size(&load_config, &types(5), Database::Sqlite), 1000);
+        assert_eq!(batch_size(&load_config, &types(40), Database::Sqlite), 819);
+        assert_eq!(
+            batch_size(&load_config, &types(40), Database::Postgres),
+            1000
+        );
+        assert_eq!(batch_size(&load_config, &types(100), Database::MySQL), 655);
+        assert_eq!(
+            batch_size(&load_config, &types(40_000), Database::Sqlite),
+            1
+        );
+        assert_eq!(batch_size(&load_config, &[], Database::Sqlite), 1000);
+    }
+
+    #[test]
+    fn test_split_batches() {
+        let rows = ["a", "bb", "c", "ddddddddddddddddddd", "e"]
+            .map(|s| vec![Data::String(s.to_owned()), Data::Empty]);
+        let lens = |batches: Vec<&[Vec<Data>]>| batches.iter().map(|b| b.len()).collect::<Vec<_>>();
+        assert_eq!(lens(split_batches(&rows, 2, None, 0)), [2, 2, 1]);
+        // A row takes its text plus 4 bytes and 6 for the empty cell.
+        assert_eq!(lens(split_batches(&rows, 10, Some(24), 0)), [2, 1, 1, 1]);
+        assert_eq!(lens(split_batches(&rows, 2, Some(1), 0)), [1, 1, 1, 1, 1]);
+        assert!(split_batches::<Vec<Data>>(&[], 2, Some(1), 0).is_empty());
+    }
+
+    #[test]
+    fn test_batch_inserter_statements() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = ExcelConfigBuilder::new(path).build().unwrap();
+        let (schema, _) = read_schema(&config, &{
+            let mut workbook = open_config_workbook(&config).unwrap();
+            read_sheet(&mut workbook, &config).unwrap()
+        })
+        .unwrap();
+        let load_config = crate::config::LoadConfigBuilder::new()
+            .batch_size(2)
+            .build()
+            .unwrap();
+        let inserter = BatchInserter::new(&config, &load_config, Database::Postgres, &schema);
+        assert_eq!(inserter.insert_cmd.matches("), (").count(), 1);
+        assert!(!inserter.row_cmd.contains("), ("));
+        let load_config = crate::config::LoadConfigBuilder::new()
+            .batch_size(1)
+            .build()
+            .unwrap();
+        let inserter = BatchInserter::new(&config, &load_config, Database::Postgres, &schema);
+        assert_eq!(inserter.insert_cmd, inserter.row_cmd);
+    }
+
+    #[test]
+    fn test_set_null_date() {
+        let mut range = Range::new((0, 0), (2, 1));
+        range.set_value((0, 0), Data::String("id".to_owned()));
+        range.set_value((0, 1), Data::String("due".to_owned()));
+        range.set_value((1, 0), Data::Float(1.0));
+        range.set_value((1, 1), Data::DateTimeIso("2023-03-15".to_owned()));
+        range.set_value((2, 0), Data::Float(2.0));
+        range.set_value((2, 1), Data::String("N/A".to_owned()));
+        let config = ExcelConfigBuilder::for_range()
+            .table_name("due")
+            .column_types(HashMap::from([("due".to_owned(), ExcelDataType::Date)]))
+            .build()
+            .unwrap();
+        let (schema, _) = read_schema(&config, &range).unwrap();
+
+        let load_config = crate::config::LoadConfigBuilder::new()
+            .on_type_error(OnTypeError::SetNull)
+            .build()
+            .unwrap();
+        let mut inserter = BatchInserter::new(&config, &load_config, Database::Sqlite, &schema);
+        let mut row = vec![Data::Float(2.0), Data::String("N/A".to_owned())];
+        inserter.check_row(&mut row, 3).unwrap();
+        assert_eq!(row[1], Data::Empty);
+        assert_eq!(inserter.nulled, 1);
+
+        let mut inserter =
+            BatchInserter::new(&config, &LoadConfig::default(), Database::Sqlite, &schema);
+        let mut row = vec![Data::Float(2.0), Data::String("N/A".to_owned())];
+        assert!(matches!(
+            inserter.check_row(&mut row, 3),
+            Err(Error::CellTypeError { row: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_union() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/months.xlsx");
+        let config = ExcelConfigBuilder::new(path)
+            .table_name("months")
+            .build()
+            .unwrap();
+        let sheets = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let (schema, parts, skipped) =
+            read_union(&config, &sheets(&["Jan", "Feb", "Mar"]), false).unwrap();
+        assert_eq!(schema.names(), ["id", "name", "amount"]);
+        assert_eq!(schema.columns[2].data_type, ExcelDataType::Float);
+        assert!(schema.columns[2].nullable);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[1].0.sheet(), "Feb");
+        assert_eq!(parts[1].0.table_name(), "months");
+        assert!(skipped.is_empty());
+
+        let error = read_union(&config, &sheets(&["Jan", "Apr", "May"]), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("column 2: `name` in `Jan`, `label` in `Apr`"));
+        assert!(error.contains("column `amount`: Float in `Jan`, String in `Apr`"));
+        assert!(error.contains("column 3: `amount` in `Jan`, missing in `May`"));
+        assert!(read_union(&config, &[], false).is_err());
+        assert!(read_union(&config, &sheets(&["Jan", "Jun"]), false).is_err());
+
+        let (_, parts, skipped) =
+            read_union(&config, &sheets(&["Jun", "Jan", "Feb", "Jul"]), true).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].0.sheet(), "Jan");
+        assert_eq!(skipped, ["Jun", "Jul"]);
+        assert!(read_union(&config, &sheets(&["Jun"]), true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_bigint() {
+        let file = std::env::temp_dir().join("excel2sql-bigint.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+        let mut range = Range::new((0, 0), (2, 0));
+        range.set_value((0, 0), Data::String("id".to_owned()));
+        range.set_value((1, 0), Data::Float(5_000_000_000.0));
+        range.set_value((2, 0), Data::Int(-5_000_000_001));
+        let excel_config = ExcelConfigBuilder::for_range()
+            .table_name("ids")
+            .build()
+            .unwrap();
+
+        let schema = read_header(&excel_config, &range).unwrap();
+        assert_eq!(schema.columns[0].data_type, ExcelDataType::Int);
+        for database in [Database::MySQL, Database::Postgres] {
+            assert_eq!(crate::sql::sql_type(ExcelDataType::Int, database), "BIGINT");
+        }
+
+        let report = load_range(&range, &excel_config, &db_config, &LoadConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(report.rows_inserted, 2);
+        let pool = db_config.connect().await.unwrap();
+        let ids: Vec<(i64,)> = sqlx::query_as("SELECT id FROM \"ids\" ORDER BY id DESC")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(ids, [(5_000_000_000,), (-5_000_000_001,)]);
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_skip_missing_sheets() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/months.xlsx");
+        let db_config = DatabaseConfig::new("sqlite::memory:").unwrap();
+        let sheets = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let skip = crate::config::LoadConfigBuilder::new()
+            .skip_missing_sheets(true)
+            .build()
+            .unwrap();
+
+        let excel_config = ExcelConfigBuilder::new(path)
+            .table_name("months")
+            .build()
+            .unwrap();
+        let union = sheets(&["Jan", "Jun", "Feb"]);
+        assert!(
+            excel2sql_union_with_config(&excel_config, &union, &db_config, &LoadConfig::default())
+                .await
+                .is_err()
+        );
+        let report = excel2sql_union_with_config(&excel_config, &union, &db_config, &skip)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped_sheets, ["Jun"]);
+        assert!(report.rows_inserted > 0);
+
+        let names = sheets(&["Jul", "Mar", "Apr"]);
+        let error = excel2sql_sheets_with_config(path, &names, &db_config, &LoadConfig::default())
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Sheets not in the workbook: Jul")
+        );
+        let report = excel2sql_sheets_with_config(path, &names, &db_config, &skip)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped_sheets, ["Jul"]);
+        let loaded = report
+            .sheets
+            .iter()
+            .map(|(name, result)| (name.as_str(), result.as_ref().unwrap().table_name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, [("Mar", "Mar"), ("Apr", "Apr")]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_strings() {
+        let file = std::env::temp_dir().join("excel2sql-empty-strings.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+        let mut range = Range::new((0, 0), (2, 1));
+        range.set_value((0, 0), Data::String("id".to_owned()));
+        range.set_value((0, 1), Data::String("note".to_owned()));
+        range.set_value((1, 0), Data::Int(1));
+        range.set_value((1, 1), Data::String(String::new()));
+        range.set_value((2, 0), Data::Int(2));
+        range.set_value((2, 1), Data::String("x".to_owned()));
+
+        for (table, empty_string_as_null) in [("nulls", true), ("empty", false)] {
+            let config = ExcelConfigBuilder::for_range()
+                .table_name(table)
+                .empty_string_as_null(empty_string_as_null)
+                .build()
+                .unwrap();
+            load_range(&range, &config, &db_config, &LoadConfig::default())
+                .await
+                .unwrap();
+        }
+        let pool = db_config.connect().await.unwrap();
+        for (table, note) in [("nulls", None), ("empty", Some(""))] {
+            let stored: Option<String> =
+                sqlx::query_scalar(&format!("SELECT note FROM {} WHERE id = 1", table))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(stored.as_deref(), note, "{}", table);
+        }
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_has_header() {
+        let file = std::env::temp_dir().join("excel2sql-has-header.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+        let mut range = Range::new((0, 0), (1, 1));
+        range.set_value((0, 0), Data::Int(1));
+        range.set_value((0, 1), Data::String("alice".to_owned()));
+        range.set_value((1, 0), Data::Int(2));
+        range.set_value((1, 1), Data::String("bob".to_owned()));
+
+        let config = ExcelConfigBuilder::for_range()
+            .table_name("generated")
+            .has_header(false)
+            .build()
+            .unwrap();
+        let report = load_range(&range, &config, &db_config, &LoadConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(report.rows_inserted, 2);
+        let config = ExcelConfigBuilder::for_range()
+            .table_name("named")
+            .has_header(false)
+            .headers(["id", "name"].map(String::from))
+            .build()
+            .unwrap();
+        load_range(&range, &config, &db_config, &LoadConfig::default())
+            .await
+            .unwrap();
+
+        let pool = db_config.connect().await.unwrap();
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT col1, col2 FROM generated ORDER BY col1")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, [(1, "alice".to_owned()), (2, "bob".to_owned())]);
+        let rows: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM named ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, [(1, "alice".to_owned()), (2, "bob".to_owned())]);
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_streaming() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let file = std::env::temp_dir().join("excel2sql-streaming.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+        let pool = db_config.connect().await.unwrap();
+
+        for name in ["simple.xlsx", "simple.xlsb", "blank_rows.xlsx"] {
+            let path = format!("{}/{}", fixtures, name);
+            let config = |table| {
+                ExcelConfigBuilder::new(&path)
+                    .table_name(table)
+                    .build()
+                    .unwrap()
+            };
+            let read = excel2sql(&config("read"), &db_config).await.unwrap();
+            let streamed = excel2sql_streaming(&config("streamed"), &db_config)
+                .await
+                .unwrap();
+            assert_eq!(streamed.columns, read.columns, "{}", name);
+            assert_eq!(streamed.rows_inserted, read.rows_inserted, "{}", name);
+            for statement in [
+                "SELECT COUNT(*) FROM (SELECT * FROM streamed EXCEPT SELECT * FROM read)",
+                "SELECT COUNT(*) FROM (SELECT * FROM read EXCEPT SELECT * FROM streamed)",
+            ] {
+                let differing: i64 = sqlx::query_scalar(statement)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                assert_eq!(differing, 0, "{}", name);
+            }
+            for table in ["read", "streamed"] {
+                query(&format!("DROP TABLE {}", table))
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let config = ExcelConfigBuilder::new(format!("{}/simple.ods", fixtures))
+            .build()
+            .unwrap();
+        assert!(excel2sql_streaming(&config, &db_config).await.is_err());
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_all_sheets() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/empty_sheet.xlsx"
+        );
+        let file = std::env::temp_dir().join("excel2sql-all-sheets-empty.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+
+        let results = excel2sql_all_sheets(path, &db_config).await.unwrap();
+        let loaded = results
+            .iter()
+            .map(|(name, result)| (name.as_str(), result.as_ref().unwrap().rows_inserted))
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, [("Data", 2), ("More", 1)]);
+        let pool = db_config.connect().await.unwrap();
+        let tables: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(tables, ["Data", "More"]);
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[tokio::test]
+    async fn test_all_sheets_failing_sheet() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/months.xlsx");
+        let file = std::env::temp_dir().join("excel2sql-all-sheets.db");
+        let _ = std::fs::remove_file(&file);
+        let db_config = DatabaseConfig::new(&format!("sqlite://{}", file.display())).unwrap();
+        let pool = db_config.connect().await.unwrap();
+        query("CREATE TABLE \"Feb\" (other INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let results = excel2sql_all_sheets(path, &db_config).await.unwrap();
+        let names = results
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Jan", "Feb", "Mar", "Apr", "May"]);
+        for (name, result) in &results {
+            match result {
+                Ok(report) => assert!(name != "Feb" && report.rows_inserted > 0),
+                Err(error) => assert_eq!(name, "Feb", "{}", error),
+            }
+        }
+        assert!(results[1].1.is_err());
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM \"May\"")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count as u64, results[4].1.as_ref().unwrap().rows_inserted);
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_blank_rows() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/blank_rows.xlsx"
+        );
+        let csv = |config: ExcelConfig| {
+            let mut output = Vec::new();
+            excel2csv(&config, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let config = ExcelConfigBuilder::new(path).build().unwrap();
+        assert_eq!(csv(config), "id,name\r\n1,a\r\n2,b\r\n3,c\r\n");
+        let config = ExcelConfigBuilder::new(path)
+            .keep_blank_rows(true)
+            .build()
+            .unwrap();
+        assert_eq!(csv(config).lines().count(), 8);
+        let config = ExcelConfigBuilder::new(path)
+            .max_blank_rows(3)
+            .build()
+            .unwrap();
+        assert_eq!(csv(config), "id,name\r\n1,a\r\n2,b\r\n");
+        assert!(
+            ExcelConfigBuilder::new(path)
+                .max_blank_rows(0)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_list_sheets() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        assert_eq!(list_sheets(path).unwrap(), ["Sheet1", "Other"]);
+        assert!(matches!(
+            list_sheets("missing.xlsx"),
+            Err(Error::ExcelConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{config::CsvFormat, utils::ExcelDataType, value::CellValue};
+use calamine::Data;
+
+pub(crate) fn csv_record<I, S>(fields: I, format: &CsvFormat) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut record = String::new();
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            record.push(format.delimiter());
+        }
+        quote_field(&mut record, field.as_ref(), format);
+    }
+    record.push_str(format.line_terminator());
+    record
+}
+
+/// Formats a cell like the value bound for `data_type`, NULL becoming an
+/// empty field.
+pub(crate) fn csv_value(
+    cell: &Data,
+    data_type: ExcelDataType,
+    empty_string_as_null: bool,
+) -> String {
+    CellValue::from_data(cell, data_type, empty_string_as_null)
+        .text()
+        .unwrap_or_default()
+}
+
+// A custom line terminator has to be quoted as well, or the field would end
+// the record early.
+fn quote_field(record: &mut String, field: &str, format: &CsvFormat) {
+    let quote = format.quote();
+    let needs_quotes = field.contains([format.delimiter(), quote, '\r', '\n'])
+        || field.contains(format.line_terminator());
+    if !needs_quotes {
+        record.push_str(field);
+        return;
+    }
+    record.push(quote);
+    for c in field.chars() {
+        if c == quote {
+            record.push(quote);
+        }
+        record.push(c);
+    }
+    record.push(quote);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_record() {
+        let rfc = CsvFormat::default();
+        assert_eq!(
+            csv_record(["1", "a,b", "say \"hi\"", ""], &rfc),
+            "1,\"a,b\",\"say \"\"hi\"\"\",\r\n"
+        );
+        let semicolon = CsvFormat::new(';', '\'', "\n").unwrap();
+        assert_eq!(
+            csv_record(["a,b", "c;d", "it's", "two\nlines"], &semicolon),
+            "a,b;'c;d';'it''s';'two\nlines'\n"
+        );
+        let tab = CsvFormat::new('\t', '"', "|").unwrap();
+        assert_eq!(
+            csv_record(["a\tb", "c|d", "e"], &tab),
+            "\"a\tb\"\t\"c|d\"\te|"
+        );
+        assert!(CsvFormat::new(',', ',', "\n").is_err());
+        assert!(CsvFormat::new('\n', '"', "\r\n").is_err());
+        assert!(CsvFormat::new(',', '"', "").is_err());
+        assert_eq!(
+            csv_value(&Data::Float(45000.5), ExcelDataType::DateTime, true),
+            "2023-03-15T12:00:00"
+        );
+        assert_eq!(csv_value(&Data::Empty, ExcelDataType::Int, true), "");
+        assert_eq!(
+            csv_value(&Data::Bool(true), ExcelDataType::Bool, true),
+            "true"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utils::ExcelDataType;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -6,8 +8,40 @@ pub enum Error {
     DatabaseConfigError(String),
     #[error(transparent)]
     DatabaseError(#[from] sqlx::Error),
+    #[error("Cannot connect to {url}: {source}")]
+    ConnectError {
+        url: String,
+        #[source]
+        source: sqlx::Error,
+    },
     #[error("Excel Config Error: {0}.")]
     ExcelConfigError(String),
+    #[error("Failed to insert rows {start} to {end}: {source}")]
+    InsertError {
+        start: usize,
+        end: usize,
+        #[source]
+        source: sqlx::Error,
+    },
+    #[error("Row {row}, column `{column}`: `{value}` is not a valid {data_type:?}")]
+    CellTypeError {
+        row: usize,
+        column: String,
+        value: String,
+        data_type: ExcelDataType,
+    },
+    #[error("Row {row} was rejected: {reason}")]
+    RowRejected { row: usize, reason: String },
+    #[error("Expected {expected} new rows in `{table}`, counted {counted}")]
+    RowCountMismatch {
+        table: String,
+        expected: u64,
+        counted: u64,
+    },
+    #[error("Load Config Error: {0}.")]
+    LoadConfigError(String),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
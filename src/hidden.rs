@@ -0,0 +1,205 @@
+use crate::{Result, utils::WorkbookBytes};
+use calamine::XlsxError;
+use quick_xml::{
+    Reader,
+    events::{Event, attributes::AttrError},
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Cursor, Read, Seek},
+    path::Path,
+};
+use zip::{ZipArchive, result::ZipError};
+
+/// Positions of the hidden columns of `sheet`, counted from `start_col`.
+///
+/// calamine does not report hidden columns, so they are read from the
+/// `<cols>` of the sheet XML. Only xlsx and xlsm keep them there, for every
+/// other format no column is hidden.
+pub(crate) fn hidden_columns(
+    path: &Path,
+    bytes: Option<&WorkbookBytes>,
+    sheet: &str,
+    start_col: u32,
+) -> Result<Vec<usize>> {
+    let hidden = match bytes {
+        Some(bytes) => read_hidden(Cursor::new(bytes.0.clone()), sheet)?,
+        None => read_hidden(File::open(path)?, sheet)?,
+    };
+    Ok(hidden
+        .into_iter()
+        .filter_map(|col| col.checked_sub(start_col))
+        .map(|col| col as usize)
+        .collect())
+}
+
+fn read_hidden<RS: Read + Seek>(reader: RS, sheet: &str) -> Result<Vec<u32>> {
+    let Ok(mut zip) = ZipArchive::new(reader) else {
+        return Ok(Vec::new());
+    };
+    let Some(id) = sheet_id(&mut zip, sheet)? else {
+        return Ok(Vec::new());
+    };
+    let Some(path) = sheet_path(&mut zip, &id)? else {
+        return Ok(Vec::new());
+    };
+    let Some(mut xml) = xml_reader(&mut zip, &path)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut hidden = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"col" => {
+                let value = |name: &str| -> Result<Option<String>> {
+                    let attribute = e.try_get_attribute(name).map_err(attr_error)?;
+                    Ok(attribute
+                        .map(|a| a.decode_and_unescape_value(xml.decoder()))
+                        .transpose()
+                        .map_err(xml_error)?
+                        .map(|v| v.into_owned()))
+                };
+                if !matches!(value("hidden")?.as_deref(), Some("1" | "true")) {
+                    continue;
+                }
+                let number = |v: Option<String>| v.and_then(|v| v.parse::<u32>().ok());
+                if let (Some(min), Some(max)) = (number(value("min")?), number(value("max")?)) {
+                    hidden.extend(min.saturating_sub(1)..max);
+                }
+            }
+            // The columns come before the cells, there is no need to read those.
+            Event::Start(e) if e.local_name().as_ref() == b"sheetData" => break,
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(hidden)
+}
+
+// The relationship id of `sheet` in `xl/workbook.xml`.
+pub(crate) fn sheet_id<RS: Read + Seek>(
+    zip: &mut ZipArchive<RS>,
+    sheet: &str,
+) -> Result<Option<Vec<u8>>> {
+    let Some(mut xml) = xml_reader(zip, "xl/workbook.xml")? else {
+        return Ok(None);
+    };
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                let mut name = None;
+                let mut id = None;
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(attr_error)?;
+                    match attribute.key.as_ref() {
+                        b"name" => {
+                            name = Some(
+                                attribute
+                                    .decode_and_unescape_value(xml.decoder())
+                                    .map_err(xml_error)?
+                                    .into_owned(),
+                            )
+                        }
+                        _ if attribute.key.local_name().as_ref() == b"id" => {
+                            id = Some(attribute.value.into_owned())
+                        }
+                        _ => (),
+                    }
+                }
+                if name.as_deref() == Some(sheet) {
+                    return Ok(id);
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => (),
+        }
+    }
+}
+
+// The zip path of the part with relationship `id`, from the workbook rels.
+pub(crate) fn sheet_path<RS: Read + Seek>(
+    zip: &mut ZipArchive<RS>,
+    id: &[u8],
+) -> Result<Option<String>> {
+    let Some(mut xml) = xml_reader(zip, "xl/_rels/workbook.xml.rels")? else {
+        return Ok(None);
+    };
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf).map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                let matches = e
+                    .try_get_attribute("Id")
+                    .map_err(attr_error)?
+                    .is_some_and(|a| a.value.as_ref() == id);
+                if !matches {
+                    continue;
+                }
+                let Some(target) = e.try_get_attribute("Target").map_err(attr_error)? else {
+                    return Ok(None);
+                };
+                let target = target
+                    .decode_and_unescape_value(xml.decoder())
+                    .map_err(xml_error)?;
+                // Targets are relative to `xl/` unless they start at the root.
+                return Ok(Some(match target.strip_prefix('/') {
+                    Some(path) => path.to_owned(),
+                    None if target.starts_with("xl/") => target.into_owned(),
+                    None => format!("xl/{}", target),
+                }));
+            }
+            Event::Eof => return Ok(None),
+            _ => (),
+        }
+    }
+}
+
+pub(crate) fn xml_reader<'a, RS: Read + Seek>(
+    zip: &'a mut ZipArchive<RS>,
+    path: &str,
+) -> Result<Option<Reader<impl BufRead + 'a>>> {
+    match zip.by_name(path) {
+        Ok(file) => Ok(Some(Reader::from_reader(BufReader::new(file)))),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(calamine::Error::Xlsx(XlsxError::Zip(e)).into()),
+    }
+}
+
+pub(crate) fn xml_error(error: quick_xml::Error) -> calamine::Error {
+    calamine::Error::Xlsx(XlsxError::Xml(error))
+}
+
+pub(crate) fn attr_error(error: AttrError) -> calamine::Error {
+    calamine::Error::Xlsx(XlsxError::XmlAttr(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_columns() {
+        let path = Path::new("tests/fixtures/hidden_columns.xlsx");
+        assert_eq!(hidden_columns(path, None, "Sheet1", 0).unwrap(), vec![1, 3]);
+        assert_eq!(hidden_columns(path, None, "Sheet1", 2).unwrap(), vec![1]);
+        assert!(hidden_columns(path, None, "Visible", 0).unwrap().is_empty());
+
+        let bytes = WorkbookBytes(std::fs::read(path).unwrap().into());
+        assert_eq!(
+            hidden_columns(path, Some(&bytes), "Sheet1", 0).unwrap(),
+            vec![1, 3]
+        );
+        for path in ["tests/fixtures/simple.xlsb", "tests/fixtures/simple.xls"] {
+            assert!(
+                hidden_columns(Path::new(path), None, "Sheet1", 0)
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+}
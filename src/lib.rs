@@ -1,6 +1,18 @@
 pub mod error;
 pub use error::*;
+pub(crate) mod comments;
 pub mod config;
+#[cfg(feature = "postgres")]
+pub(crate) mod copy;
 pub(crate) mod core;
+pub(crate) mod csv;
+pub(crate) mod hidden;
+pub(crate) mod log;
+pub(crate) mod report;
+pub use report::*;
+pub mod sink;
+pub mod sql;
+pub(crate) mod stream;
 pub mod utils;
+pub(crate) mod value;
 pub use core::*;
@@ -0,0 +1,25 @@
+//! Logging through `tracing` when the `tracing` feature is enabled. Without
+//! it every event compiles to nothing and the crate stays silent.
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)*);
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)*);
+    };
+}
+
+pub(crate) use {debug, info, warning};
@@ -0,0 +1,42 @@
+use crate::Result;
+use std::time::Duration;
+
+/// Summary of loading one sheet into a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LoadReport {
+    pub table_name: String,
+    pub columns: Vec<String>,
+    /// Data rows read from the sheet.
+    pub rows_read: u64,
+    /// Rows the database reported as inserted or updated.
+    pub rows_inserted: u64,
+    /// Rows read but never sent to the database.
+    pub rows_skipped: u64,
+    /// Cells parsed by [`OnTypeError::Coerce`](crate::config::OnTypeError::Coerce).
+    pub cells_coerced: u64,
+    /// Cells stored as NULL because they did not fit their column's type.
+    pub cells_set_null: u64,
+    /// Rows refused by [`row_check`](crate::config::LoadConfigBuilder::row_check).
+    pub rows_rejected: u64,
+    /// Rows skipped by [`collect_errors`](crate::config::LoadConfigBuilder::collect_errors),
+    /// as row number and reason.
+    pub row_errors: Vec<(usize, String)>,
+    /// Rows sent to the database, when [`verify`](crate::config::LoadConfigBuilder::verify)
+    /// counted the table.
+    pub rows_expected: Option<u64>,
+    /// Rows the load added to the table by `SELECT COUNT(*)`.
+    pub rows_counted: Option<u64>,
+    /// Sheets left out by [`skip_missing_sheets`](crate::config::LoadConfigBuilder::skip_missing_sheets).
+    pub skipped_sheets: Vec<String>,
+    pub elapsed: Duration,
+}
+
+/// Summary of loading several sheets of a workbook, each into its own table.
+#[derive(Debug, Default)]
+pub struct SheetsReport {
+    /// Each loaded sheet next to its result, in the order the sheets were given.
+    pub sheets: Vec<(String, Result<LoadReport>)>,
+    /// Sheets left out by [`skip_missing_sheets`](crate::config::LoadConfigBuilder::skip_missing_sheets).
+    pub skipped_sheets: Vec<String>,
+}
@@ -0,0 +1,21 @@
+use crate::{Result, utils::TableSchema};
+use calamine::Data;
+use std::future::Future;
+
+/// Destination for the rows of a sheet, driven by [`excel2sink`](crate::excel2sink):
+/// `begin` is called once with the inferred schema, `write_row` for every
+/// data row and `finish` at the end.
+pub trait RowSink {
+    fn begin(&mut self, schema: &TableSchema) -> impl Future<Output = Result<()>> + Send;
+
+    fn write_row(&mut self, row: &[Data]) -> impl Future<Output = Result<()>> + Send;
+
+    /// Called instead of `write_row` for a blank data row that is skipped,
+    /// so row numbers stay in step with the sheet.
+    fn skip_row(&mut self) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Flushes whatever is buffered and returns the number of rows stored.
+    fn finish(&mut self) -> impl Future<Output = Result<u64>> + Send;
+}
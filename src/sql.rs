@@ -0,0 +1,1014 @@
+use crate::{
+    config::Database,
+    utils::{ColumnSchema, ExcelDataType, TableSchema},
+    value::CellValue,
+};
+use calamine::Data;
+
+pub fn sql_type(data_type: ExcelDataType, database: Database) -> String {
+    let sql_type = match (data_type, database) {
+        (ExcelDataType::Int, Database::MySQL | Database::Postgres) => "BIGINT",
+        (ExcelDataType::Int, Database::Sqlite) => "INTEGER",
+        (ExcelDataType::Float, Database::MySQL) => "DOUBLE",
+        (ExcelDataType::Float, Database::Postgres) => "DOUBLE PRECISION",
+        (ExcelDataType::Float, Database::Sqlite) => "REAL",
+        (ExcelDataType::String, _) => "TEXT",
+        (ExcelDataType::Bool, Database::MySQL) => "TINYINT(1)",
+        (ExcelDataType::Bool, Database::Postgres | Database::Sqlite) => "BOOLEAN",
+        (ExcelDataType::Date, _) => "DATE",
+        (ExcelDataType::Time, _) => "TIME",
+        (ExcelDataType::DateTime, Database::MySQL) => "DATETIME",
+        (ExcelDataType::DateTime, Database::Postgres | Database::Sqlite) => "TIMESTAMP",
+        (ExcelDataType::Duration, Database::MySQL) => "TIME",
+        (ExcelDataType::Duration, Database::Postgres) => "INTERVAL",
+        (ExcelDataType::Duration, Database::Sqlite) => "TEXT",
+        (ExcelDataType::Decimal { precision, scale }, Database::MySQL) => {
+            return format!("DECIMAL({}, {})", precision, scale);
+        }
+        (ExcelDataType::Decimal { precision, scale }, Database::Postgres | Database::Sqlite) => {
+            return format!("NUMERIC({}, {})", precision, scale);
+        }
+        (ExcelDataType::NULL, _) => "TEXT",
+    };
+    sql_type.to_owned()
+}
+
+/// Quotes a table or column name for `database`, escaping embedded quotes.
+pub fn quote_identifier(name: &str, database: Database) -> String {
+    match database {
+        Database::MySQL => format!("`{}`", name.replace('`', "``")),
+        Database::Postgres | Database::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Type of `column` in the `CREATE TABLE` statement.
+pub(crate) fn column_sql_type(column: &ColumnSchema, database: Database) -> String {
+    match (database, column.data_type) {
+        // MySQL cannot index a TEXT column without a prefix length.
+        (Database::MySQL, ExcelDataType::String | ExcelDataType::NULL) if column.is_primary_key => {
+            "VARCHAR(255)".to_owned()
+        }
+        _ => sql_type(column.data_type, database),
+    }
+}
+
+// ` DEFAULT <value>` clause of `column`, empty without a default.
+fn default_sql(column: &ColumnSchema, database: Database) -> String {
+    let Some(cell) = column.default_cell() else {
+        return String::new();
+    };
+    let value = literal(&cell, column.data_type, database, false);
+    match database {
+        // MySQL only takes a default for a TEXT column as an expression.
+        Database::MySQL if column_sql_type(column, database) == "TEXT" => {
+            format!(" DEFAULT ({})", value)
+        }
+        _ => format!(" DEFAULT {}", value),
+    }
+}
+
+/// A table name, optionally qualified by the schema (or MySQL database) it
+/// lives in.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TableName<'a> {
+    pub(crate) schema: Option<&'a str>,
+    pub(crate) name: &'a str,
+}
+
+impl<'a> From<&'a str> for TableName<'a> {
+    fn from(name: &'a str) -> Self {
+        Self { schema: None, name }
+    }
+}
+
+impl TableName<'_> {
+    fn quoted(self, database: Database) -> String {
+        match self.schema {
+            Some(schema) => format!(
+                "{}.{}",
+                quote_identifier(schema, database),
+                quote_identifier(self.name, database)
+            ),
+            None => quote_identifier(self.name, database),
+        }
+    }
+}
+
+/// `CREATE SCHEMA` for Postgres. MySQL schemas are databases, which are left
+/// to the user, and SQLite has none.
+pub(crate) fn create_schema_sql(schema: &str, database: Database) -> Option<String> {
+    match database {
+        Database::Postgres => Some(format!(
+            "CREATE SCHEMA IF NOT EXISTS {};",
+            quote_identifier(schema, database)
+        )),
+        Database::MySQL | Database::Sqlite => None,
+    }
+}
+
+/// `CREATE TABLE` for `schema`, with a leading auto-incrementing primary key
+/// named `id_column` and a trailing `loaded_at_column` timestamp if given.
+/// The `charset` and `collation` only apply to MySQL.
+pub(crate) fn create_table_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    schema: &TableSchema,
+    database: Database,
+    id_column: Option<&str>,
+    loaded_at_column: Option<&str>,
+    charset: Option<&str>,
+    collation: Option<&str>,
+) -> String {
+    let id_column = id_column.map(|name| {
+        let name = quote_identifier(name, database);
+        match database {
+            Database::MySQL => format!("{} BIGINT AUTO_INCREMENT PRIMARY KEY", name),
+            Database::Postgres => format!("{} BIGSERIAL PRIMARY KEY", name),
+            Database::Sqlite => format!("{} INTEGER PRIMARY KEY AUTOINCREMENT", name),
+        }
+    });
+    let mut columns = id_column
+        .into_iter()
+        .chain(schema.columns.iter().map(|column| {
+            let not_null = if column.nullable { "" } else { " NOT NULL" };
+            let comment = match (&column.comment, database) {
+                (Some(comment), Database::MySQL) => {
+                    format!(" COMMENT {}", quote_string(comment, database))
+                }
+                _ => String::new(),
+            };
+            format!(
+                "{} {}{}{}{}",
+                quote_identifier(&column.name, database),
+                column_sql_type(column, database),
+                not_null,
+                default_sql(column, database),
+                comment
+            )
+        }))
+        .chain(loaded_at_column.map(|name| {
+            format!(
+                "{} {} NOT NULL",
+                quote_identifier(name, database),
+                sql_type(ExcelDataType::DateTime, database)
+            )
+        }))
+        .collect::<Vec<_>>();
+    let table = table.into();
+    let primary_key = schema.primary_key();
+    if !primary_key.is_empty() {
+        columns.push(format!(
+            "PRIMARY KEY ({})",
+            quote_identifiers(&primary_key, database)
+        ));
+    }
+    // A table constraint, MySQL ignores `REFERENCES` on a column.
+    for column in &schema.columns {
+        if let Some((ref_table, ref_column)) = &column.references {
+            let ref_table = TableName {
+                schema: table.schema,
+                name: ref_table,
+            };
+            columns.push(format!(
+                "FOREIGN KEY ({}) REFERENCES {} ({})",
+                quote_identifier(&column.name, database),
+                ref_table.quoted(database),
+                quote_identifier(ref_column, database)
+            ));
+        }
+    }
+
+    let mut options = String::new();
+    if database == Database::MySQL {
+        if let Some(charset) = charset {
+            options.push_str(&format!(" DEFAULT CHARSET={}", charset));
+        }
+        if let Some(collation) = collation {
+            options.push_str(&format!(" COLLATE={}", collation));
+        }
+    }
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} ({}){};",
+        table.quoted(database),
+        columns.join(", "),
+        options
+    )
+}
+
+/// `COMMENT ON COLUMN` for each column with a comment in Postgres. MySQL
+/// takes them in `CREATE TABLE` instead, SQLite has none.
+pub(crate) fn comment_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    schema: &TableSchema,
+    database: Database,
+) -> Vec<String> {
+    if database != Database::Postgres {
+        return Vec::new();
+    }
+    let table = table.into();
+    schema
+        .columns
+        .iter()
+        .filter_map(|column| {
+            Some(format!(
+                "COMMENT ON COLUMN {}.{} IS {};",
+                table.quoted(database),
+                quote_identifier(&column.name, database),
+                quote_string(column.comment.as_deref()?, database)
+            ))
+        })
+        .collect()
+}
+
+/// `CREATE INDEX` on `columns`, named after the table and the columns. MySQL
+/// can only index text columns by a prefix.
+pub(crate) fn create_index_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    columns: &[String],
+    schema: &TableSchema,
+    database: Database,
+) -> String {
+    let table = table.into();
+    let name = quote_identifier(&index_name(table.name, columns), database);
+    let key_parts = columns
+        .iter()
+        .map(|column| {
+            let is_text = schema.columns.iter().any(|c| {
+                c.name == *column
+                    && matches!(c.data_type, ExcelDataType::String | ExcelDataType::NULL)
+            });
+            match database {
+                Database::MySQL if is_text => {
+                    format!("{}(255)", quote_identifier(column, database))
+                }
+                _ => quote_identifier(column, database),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let if_not_exists = match database {
+        Database::MySQL => "",
+        Database::Postgres | Database::Sqlite => "IF NOT EXISTS ",
+    };
+    format!(
+        "CREATE INDEX {}{} ON {} ({});",
+        if_not_exists,
+        name,
+        table.quoted(database),
+        key_parts
+    )
+}
+
+fn index_name(table: &str, columns: &[String]) -> String {
+    format!("{}_{}_idx", table, columns.join("_"))
+}
+
+/// Counts the entries of the index [`create_index_sql`] names in MySQL's
+/// `information_schema`, which has to be asked since MySQL has no
+/// `CREATE INDEX IF NOT EXISTS`.
+pub(crate) fn mysql_index_exists_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    columns: &[String],
+) -> String {
+    let table = table.into();
+    let schema = table.schema.map_or("DATABASE()".to_owned(), |schema| {
+        quote_string(schema, Database::MySQL)
+    });
+    format!(
+        "SELECT COUNT(*) FROM information_schema.statistics WHERE table_schema = {} \
+         AND table_name = {} AND index_name = {};",
+        schema,
+        quote_string(table.name, Database::MySQL),
+        quote_string(&index_name(table.name, columns), Database::MySQL)
+    )
+}
+
+pub(crate) fn drop_table_sql<'a>(table: impl Into<TableName<'a>>, database: Database) -> String {
+    format!("DROP TABLE IF EXISTS {};", table.into().quoted(database))
+}
+
+pub(crate) fn count_rows_sql<'a>(table: impl Into<TableName<'a>>, database: Database) -> String {
+    format!("SELECT COUNT(*) FROM {};", table.into().quoted(database))
+}
+
+pub(crate) fn truncate_table_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    database: Database,
+) -> String {
+    let table = table.into().quoted(database);
+    match database {
+        Database::MySQL | Database::Postgres => format!("TRUNCATE TABLE {};", table),
+        Database::Sqlite => format!("DELETE FROM {};", table),
+    }
+}
+
+/// Conflict clause turning an `INSERT` into an upsert on `keys`, empty when
+/// there are no keys. Postgres rejects a batch that updates the same key twice.
+pub(crate) fn on_conflict_sql(schema: &TableSchema, keys: &[String], database: Database) -> String {
+    if keys.is_empty() {
+        return String::new();
+    }
+
+    let updates = schema
+        .columns
+        .iter()
+        .filter(|column| !keys.contains(&column.name))
+        .map(|column| {
+            let name = quote_identifier(&column.name, database);
+            match database {
+                Database::MySQL => format!("{} = VALUES({})", name, name),
+                Database::Postgres | Database::Sqlite => format!("{} = EXCLUDED.{}", name, name),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match database {
+        // Assigning a key to itself turns a duplicate into a no-op.
+        Database::MySQL if updates.is_empty() => {
+            let key = quote_identifier(&keys[0], database);
+            format!(" ON DUPLICATE KEY UPDATE {} = {}", key, key)
+        }
+        Database::MySQL => format!(" ON DUPLICATE KEY UPDATE {}", updates.join(", ")),
+        Database::Postgres | Database::Sqlite if updates.is_empty() => {
+            format!(
+                " ON CONFLICT ({}) DO NOTHING",
+                quote_identifiers(keys, database)
+            )
+        }
+        Database::Postgres | Database::Sqlite => format!(
+            " ON CONFLICT ({}) DO UPDATE SET {}",
+            quote_identifiers(keys, database),
+            updates.join(", ")
+        ),
+    }
+}
+
+fn quote_identifiers(names: &[String], database: Database) -> String {
+    names
+        .iter()
+        .map(|name| quote_identifier(name, database))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Most bind parameters a statement may have: MySQL and Postgres refuse more
+/// than 65535, SQLite more than 32766 unless built with a higher
+/// `SQLITE_MAX_VARIABLE_NUMBER`.
+pub(crate) fn max_bind_params(database: Database) -> usize {
+    match database {
+        Database::MySQL | Database::Postgres => 65535,
+        Database::Sqlite => 32766,
+    }
+}
+
+pub(crate) fn insert_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    columns: &[String],
+    col_types: &[ExcelDataType],
+    database: Database,
+    rows: usize,
+    on_conflict: &str,
+) -> String {
+    let values = (0..rows)
+        .map(|row| {
+            let row = col_types
+                .iter()
+                .enumerate()
+                .map(|(i, data_type)| {
+                    placeholder(row * col_types.len() + i + 1, *data_type, database)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", row)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES {}{};",
+        table.into().quoted(database),
+        quote_identifiers(columns, database),
+        values,
+        on_conflict
+    )
+}
+
+pub(crate) fn insert_values_sql<'a>(
+    table: impl Into<TableName<'a>>,
+    columns: &[String],
+    rows: &[impl AsRef<[Data]>],
+    col_types: &[ExcelDataType],
+    database: Database,
+    on_conflict: &str,
+    empty_string_as_null: bool,
+) -> String {
+    let values = rows
+        .iter()
+        .map(|row| {
+            let row = row.as_ref();
+            // Short rows are padded with NULL.
+            let row = col_types
+                .iter()
+                .enumerate()
+                .map(|(i, data_type)| {
+                    literal(
+                        row.get(i).unwrap_or(&Data::Empty),
+                        *data_type,
+                        database,
+                        empty_string_as_null,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", row)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES {}{};",
+        table.into().quoted(database),
+        quote_identifiers(columns, database),
+        values,
+        on_conflict
+    )
+}
+
+#[cfg(feature = "postgres")]
+pub(crate) fn copy_sql<'a>(table: impl Into<TableName<'a>>, columns: &[String]) -> String {
+    format!(
+        "COPY {} ({}) FROM STDIN",
+        table.into().quoted(Database::Postgres),
+        quote_identifiers(columns, Database::Postgres)
+    )
+}
+
+/// Writes `row` as a line of `COPY` text data, NULL being `\N`.
+#[cfg(feature = "postgres")]
+pub(crate) fn copy_row(
+    output: &mut String,
+    row: &[Data],
+    col_types: &[ExcelDataType],
+    empty_string_as_null: bool,
+) {
+    for (i, data_type) in col_types.iter().enumerate() {
+        if i > 0 {
+            output.push('\t');
+        }
+        let cell = row.get(i).unwrap_or(&Data::Empty);
+        match CellValue::from_data(cell, *data_type, empty_string_as_null).text() {
+            Some(text) => {
+                for c in text.chars() {
+                    match c {
+                        '\\' => output.push_str("\\\\"),
+                        '\t' => output.push_str("\\t"),
+                        '\n' => output.push_str("\\n"),
+                        '\r' => output.push_str("\\r"),
+                        c => output.push(c),
+                    }
+                }
+            }
+            None => output.push_str("\\N"),
+        }
+    }
+    output.push('\n');
+}
+
+// Only for SQL scripts, loads into a database bind every cell instead.
+fn literal(
+    cell: &Data,
+    data_type: ExcelDataType,
+    database: Database,
+    empty_string_as_null: bool,
+) -> String {
+    match CellValue::from_data(cell, data_type, empty_string_as_null) {
+        CellValue::Int(v) => v.to_string(),
+        // NaN and infinity have no literal.
+        CellValue::Float(v) if v.is_finite() => v.to_string(),
+        CellValue::Bool(v) => if v { "TRUE" } else { "FALSE" }.to_owned(),
+        CellValue::Str(v) | CellValue::DateTime(v) => quote_string(&v, database),
+        CellValue::Decimal(v) => v,
+        CellValue::Float(_) | CellValue::Null(_) => "NULL".to_owned(),
+    }
+}
+
+fn quote_string(value: &str, database: Database) -> String {
+    let value = value.replace('\'', "''");
+    match database {
+        // Backslashes are escapes in MySQL strings unless NO_BACKSLASH_ESCAPES is set.
+        Database::MySQL => format!("'{}'", value.replace('\\', "\\\\")),
+        Database::Postgres | Database::Sqlite => format!("'{}'", value),
+    }
+}
+
+fn placeholder(index: usize, data_type: ExcelDataType, database: Database) -> String {
+    match database {
+        // Date, time, duration and decimal cells are bound as text, which
+        // Postgres refuses to assign to their columns without an explicit cast.
+        Database::Postgres
+            if matches!(
+                data_type,
+                ExcelDataType::Date
+                    | ExcelDataType::Time
+                    | ExcelDataType::DateTime
+                    | ExcelDataType::Duration
+                    | ExcelDataType::Decimal { .. }
+            ) =>
+        {
+            format!("CAST(${} AS {})", index, sql_type(data_type, database))
+        }
+        Database::Postgres => format!("${}", index),
+        Database::MySQL | Database::Sqlite => "?".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ColumnSchema;
+
+    fn schema(columns: &[(&str, ExcelDataType, bool, bool)]) -> TableSchema {
+        TableSchema {
+            columns: columns
+                .iter()
+                .map(
+                    |&(name, data_type, nullable, is_primary_key)| ColumnSchema {
+                        name: name.to_owned(),
+                        data_type,
+                        nullable,
+                        is_primary_key,
+                        default: None,
+                        references: None,
+                        comment: None,
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_create_table_sql() {
+        let nullable = schema(&[
+            ("id", ExcelDataType::Int, true, false),
+            ("na\"me", ExcelDataType::String, true, false),
+        ]);
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::Postgres,
+                None,
+                None,
+                None,
+                None
+            ),
+            "CREATE TABLE IF NOT EXISTS \"sheet\" (\"id\" BIGINT, \"na\"\"me\" TEXT);"
+        );
+        let not_null = schema(&[
+            ("id", ExcelDataType::Int, false, false),
+            ("na\"me", ExcelDataType::String, true, false),
+        ]);
+        assert_eq!(
+            create_table_sql("sheet", &not_null, Database::Sqlite, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS \"sheet\" (\"id\" INTEGER NOT NULL, \"na\"\"me\" TEXT);"
+        );
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::Postgres,
+                Some("row_id"),
+                None,
+                None,
+                None
+            ),
+            "CREATE TABLE IF NOT EXISTS \"sheet\" (\"row_id\" BIGSERIAL PRIMARY KEY, \"id\" BIGINT, \"na\"\"me\" TEXT);"
+        );
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::MySQL,
+                Some("row_id"),
+                None,
+                None,
+                None
+            ),
+            "CREATE TABLE IF NOT EXISTS `sheet` (`row_id` BIGINT AUTO_INCREMENT PRIMARY KEY, `id` BIGINT, `na\"me` TEXT);"
+        );
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::MySQL,
+                None,
+                Some("loaded_at"),
+                None,
+                None
+            ),
+            "CREATE TABLE IF NOT EXISTS `sheet` (`id` BIGINT, `na\"me` TEXT, `loaded_at` DATETIME NOT NULL);"
+        );
+        let charset = (Some("utf8mb4"), Some("utf8mb4_unicode_ci"));
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::MySQL,
+                None,
+                None,
+                charset.0,
+                charset.1
+            ),
+            "CREATE TABLE IF NOT EXISTS `sheet` (`id` BIGINT, `na\"me` TEXT) DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;"
+        );
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::Sqlite,
+                None,
+                None,
+                charset.0,
+                charset.1
+            ),
+            "CREATE TABLE IF NOT EXISTS \"sheet\" (\"id\" INTEGER, \"na\"\"me\" TEXT);"
+        );
+        assert_eq!(
+            create_table_sql(
+                "sheet",
+                &nullable,
+                Database::MySQL,
+                None,
+                None,
+                None,
+                charset.1
+            ),
+            "CREATE TABLE IF NOT EXISTS `sheet` (`id` BIGINT, `na\"me` TEXT) COLLATE=utf8mb4_unicode_ci;"
+        );
+    }
+
+    #[test]
+    fn test_upsert_sql() {
+        let schema = schema(&[
+            ("id", ExcelDataType::String, true, true),
+            ("name", ExcelDataType::String, true, false),
+        ]);
+        let keys = vec!["id".to_owned()];
+        assert_eq!(
+            create_table_sql("sheet", &schema, Database::MySQL, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS `sheet` (`id` VARCHAR(255), `name` TEXT, PRIMARY KEY (`id`));"
+        );
+        assert_eq!(
+            on_conflict_sql(&schema, &keys, Database::Postgres),
+            " ON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\""
+        );
+        assert_eq!(
+            on_conflict_sql(&schema, &keys, Database::MySQL),
+            " ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+        assert_eq!(on_conflict_sql(&schema, &[], Database::MySQL), "");
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("a`b", Database::MySQL), "`a``b`");
+        assert_eq!(quote_identifier("a\"b", Database::Postgres), "\"a\"\"b\"");
+        assert_eq!(quote_identifier("select", Database::Sqlite), "\"select\"");
+    }
+
+    #[test]
+    fn test_sql_type() {
+        assert_eq!(sql_type(ExcelDataType::Float, Database::MySQL), "DOUBLE");
+        assert_eq!(
+            sql_type(ExcelDataType::Float, Database::Postgres),
+            "DOUBLE PRECISION"
+        );
+        assert_eq!(sql_type(ExcelDataType::Bool, Database::MySQL), "TINYINT(1)");
+        assert_eq!(
+            sql_type(ExcelDataType::DateTime, Database::MySQL),
+            "DATETIME"
+        );
+        assert_eq!(sql_type(ExcelDataType::Date, Database::MySQL), "DATE");
+        assert_eq!(sql_type(ExcelDataType::Time, Database::Postgres), "TIME");
+        assert_eq!(
+            sql_type(ExcelDataType::Duration, Database::Postgres),
+            "INTERVAL"
+        );
+        assert_eq!(sql_type(ExcelDataType::NULL, Database::Postgres), "TEXT");
+        let decimal = ExcelDataType::Decimal {
+            precision: 18,
+            scale: 2,
+        };
+        assert_eq!(sql_type(decimal, Database::MySQL), "DECIMAL(18, 2)");
+        assert_eq!(sql_type(decimal, Database::Postgres), "NUMERIC(18, 2)");
+    }
+
+    #[test]
+    fn test_create_index_sql() {
+        let schema = schema(&[
+            ("id", ExcelDataType::Int, false, false),
+            ("name", ExcelDataType::String, true, false),
+        ]);
+        let columns = ["id".to_owned(), "name".to_owned()];
+        assert_eq!(
+            create_index_sql("sheet", &columns, &schema, Database::Postgres),
+            "CREATE INDEX IF NOT EXISTS \"sheet_id_name_idx\" ON \"sheet\" (\"id\", \"name\");"
+        );
+        assert_eq!(
+            create_index_sql("sheet", &columns, &schema, Database::MySQL),
+            "CREATE INDEX `sheet_id_name_idx` ON `sheet` (`id`, `name`(255));"
+        );
+        assert_eq!(
+            mysql_index_exists_sql("sheet", &columns),
+            "SELECT COUNT(*) FROM information_schema.statistics WHERE table_schema = DATABASE() \
+             AND table_name = 'sheet' AND index_name = 'sheet_id_name_idx';"
+        );
+        let table = TableName {
+            schema: Some("staging"),
+            name: "sheet",
+        };
+        assert!(mysql_index_exists_sql(table, &columns).contains("table_schema = 'staging'"));
+    }
+
+    #[test]
+    fn test_column_comments() {
+        let mut schema = schema(&[
+            ("id", ExcelDataType::Int, false, false),
+            ("amount", ExcelDataType::Float, true, false),
+        ]);
+        schema.columns[1].comment = Some("Net amount, in 'EUR'".to_owned());
+        assert_eq!(
+            create_table_sql("sales", &schema, Database::MySQL, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS `sales` (`id` BIGINT NOT NULL, `amount` DOUBLE COMMENT 'Net amount, in ''EUR''');"
+        );
+        assert_eq!(
+            comment_sql("sales", &schema, Database::Postgres),
+            ["COMMENT ON COLUMN \"sales\".\"amount\" IS 'Net amount, in ''EUR''';"]
+        );
+        assert!(comment_sql("sales", &schema, Database::Sqlite).is_empty());
+        assert!(comment_sql("sales", &schema, Database::MySQL).is_empty());
+    }
+
+    #[test]
+    fn test_foreign_key() {
+        let mut orders = schema(&[
+            ("id", ExcelDataType::Int, false, true),
+            ("customer_id", ExcelDataType::Int, true, false),
+        ]);
+        orders.columns[1].references = Some(("customers".to_owned(), "id".to_owned()));
+        assert_eq!(
+            create_table_sql("orders", &orders, Database::MySQL, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS `orders` (`id` BIGINT NOT NULL, `customer_id` BIGINT, PRIMARY KEY (`id`), FOREIGN KEY (`customer_id`) REFERENCES `customers` (`id`));"
+        );
+        let table = TableName {
+            schema: Some("staging"),
+            name: "orders",
+        };
+        assert_eq!(
+            create_table_sql(table, &orders, Database::Postgres, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS \"staging\".\"orders\" (\"id\" BIGINT NOT NULL, \"customer_id\" BIGINT, PRIMARY KEY (\"id\"), FOREIGN KEY (\"customer_id\") REFERENCES \"staging\".\"customers\" (\"id\"));"
+        );
+    }
+
+    #[test]
+    fn test_qualified_table() {
+        let table = TableName {
+            schema: Some("staging"),
+            name: "sheet",
+        };
+        assert_eq!(
+            drop_table_sql(table, Database::Postgres),
+            "DROP TABLE IF EXISTS \"staging\".\"sheet\";"
+        );
+        assert_eq!(
+            truncate_table_sql(table, Database::MySQL),
+            "TRUNCATE TABLE `staging`.`sheet`;"
+        );
+        assert_eq!(
+            count_rows_sql(table, Database::Sqlite),
+            "SELECT COUNT(*) FROM \"staging\".\"sheet\";"
+        );
+        assert_eq!(
+            create_schema_sql("staging", Database::Postgres).as_deref(),
+            Some("CREATE SCHEMA IF NOT EXISTS \"staging\";")
+        );
+        assert_eq!(create_schema_sql("staging", Database::MySQL), None);
+    }
+
+    #[test]
+    fn test_insert_sql() {
+        let columns = vec!["id".to_owned(), "at".to_owned()];
+        let col_types = vec![ExcelDataType::Int, ExcelDataType::DateTime];
+        assert_eq!(
+            insert_sql("sheet", &columns, &col_types, Database::Postgres, 2, ""),
+            "INSERT INTO \"sheet\" (\"id\", \"at\") VALUES ($1, CAST($2 AS TIMESTAMP)), ($3, CAST($4 AS TIMESTAMP));"
+        );
+        assert_eq!(
+            insert_sql("sheet", &columns, &col_types, Database::MySQL, 2, ""),
+            "INSERT INTO `sheet` (`id`, `at`) VALUES (?, ?), (?, ?);"
+        );
+    }
+
+    #[test]
+    fn test_insert_values_sql_ragged_rows() {
+        let columns = ["a", "b", "c"].map(String::from);
+        let col_types = vec![ExcelDataType::Int; 3];
+        let rows: [&[Data]; 2] = [
+            &[Data::Int(1)],
+            &[Data::Int(1), Data::Int(2), Data::Int(3), Data::Int(4)],
+        ];
+        assert_eq!(
+            insert_values_sql(
+                "sheet",
+                &columns,
+                &rows,
+                &col_types,
+                Database::Sqlite,
+                "",
+                true
+            ),
+            "INSERT INTO \"sheet\" (\"a\", \"b\", \"c\") VALUES (1, NULL, NULL), (1, 2, 3);"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn test_copy_row() {
+        assert_eq!(
+            copy_sql("people", &["id".to_owned(), "na\"me".to_owned()]),
+            "COPY \"people\" (\"id\", \"na\"\"me\") FROM STDIN"
+        );
+        let col_types = [
+            ExcelDataType::Int,
+            ExcelDataType::String,
+            ExcelDataType::Bool,
+            ExcelDataType::DateTime,
+            ExcelDataType::Float,
+        ];
+        let mut data = String::new();
+        copy_row(
+            &mut data,
+            &[
+                Data::Float(1.0),
+                Data::String("a\tb\\c\nd".to_owned()),
+                Data::Bool(true),
+                Data::Float(45000.5),
+            ],
+            &col_types,
+            true,
+        );
+        copy_row(
+            &mut data,
+            &[Data::Empty, Data::String(String::new())],
+            &col_types,
+            true,
+        );
+        assert_eq!(
+            data,
+            "1\ta\\tb\\\\c\\nd\ttrue\t2023-03-15T12:00:00\t\\N\n\\N\t\\N\t\\N\t\\N\t\\N\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_values_sql() {
+        let row = [
+            Data::Int(1),
+            Data::String("it's".to_owned()),
+            Data::Empty,
+            Data::Bool(true),
+        ];
+        let columns = ["a", "b", "c", "d"].map(String::from);
+        let col_types = vec![
+            ExcelDataType::Int,
+            ExcelDataType::String,
+            ExcelDataType::Float,
+            ExcelDataType::Bool,
+        ];
+        assert_eq!(
+            insert_values_sql(
+                "sheet",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::Sqlite,
+                "",
+                true
+            ),
+            "INSERT INTO \"sheet\" (\"a\", \"b\", \"c\", \"d\") VALUES (1, 'it''s', NULL, TRUE);"
+        );
+
+        let row = [Data::String(String::new())];
+        let columns = vec!["a".to_owned()];
+        let col_types = vec![ExcelDataType::String];
+        assert_eq!(
+            insert_values_sql(
+                "sheet",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::Sqlite,
+                "",
+                true
+            ),
+            "INSERT INTO \"sheet\" (\"a\") VALUES (NULL);"
+        );
+        assert_eq!(
+            insert_values_sql(
+                "sheet",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::Sqlite,
+                "",
+                false
+            ),
+            "INSERT INTO \"sheet\" (\"a\") VALUES ('');"
+        );
+    }
+
+    #[test]
+    fn test_column_defaults() {
+        let mut table = schema(&[
+            ("status", ExcelDataType::String, true, false),
+            ("n", ExcelDataType::Int, true, false),
+            ("ok", ExcelDataType::Bool, true, false),
+        ]);
+        for (column, default) in table.columns.iter_mut().zip(["it's", "0", "yes"]) {
+            column.default = Some(default.to_owned());
+        }
+        assert_eq!(
+            create_table_sql("t", &table, Database::Postgres, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS \"t\" (\"status\" TEXT DEFAULT 'it''s', \"n\" BIGINT DEFAULT 0, \"ok\" BOOLEAN DEFAULT TRUE);"
+        );
+        assert_eq!(
+            create_table_sql("t", &table, Database::MySQL, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS `t` (`status` TEXT DEFAULT ('it''s'), `n` BIGINT DEFAULT 0, `ok` TINYINT(1) DEFAULT TRUE);"
+        );
+    }
+
+    #[test]
+    fn test_sql_metacharacters() {
+        let header = "x); DROP TABLE y;--";
+        let columns = vec![header.to_owned(), "a`b".to_owned()];
+        let col_types = vec![ExcelDataType::String, ExcelDataType::Float];
+        let table = schema(&[
+            (header, ExcelDataType::String, true, false),
+            ("a`b", ExcelDataType::Float, true, false),
+        ]);
+        assert_eq!(
+            create_table_sql("t\"; --", &table, Database::Sqlite, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS \"t\"\"; --\" (\"x); DROP TABLE y;--\" TEXT, \"a`b\" REAL);"
+        );
+        assert_eq!(
+            create_table_sql("t", &table, Database::MySQL, None, None, None, None),
+            "CREATE TABLE IF NOT EXISTS `t` (`x); DROP TABLE y;--` TEXT, `a``b` DOUBLE);"
+        );
+        // Values never reach the statement of a database load.
+        assert_eq!(
+            insert_sql("t", &columns, &col_types, Database::MySQL, 1, ""),
+            "INSERT INTO `t` (`x); DROP TABLE y;--`, `a``b`) VALUES (?, ?);"
+        );
+
+        let row = [
+            Data::String("\\'); DROP TABLE y;--".to_owned()),
+            Data::String("1); DROP TABLE y;--".to_owned()),
+        ];
+        assert_eq!(
+            insert_values_sql(
+                "t",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::MySQL,
+                "",
+                true
+            ),
+            "INSERT INTO `t` (`x); DROP TABLE y;--`, `a``b`) VALUES ('\\\\''); DROP TABLE y;--', NULL);"
+        );
+        assert_eq!(
+            insert_values_sql(
+                "t",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::Postgres,
+                "",
+                true
+            ),
+            "INSERT INTO \"t\" (\"x); DROP TABLE y;--\", \"a`b\") VALUES ('\\''); DROP TABLE y;--', NULL);"
+        );
+        let row = [Data::Empty, Data::Float(f64::NAN)];
+        assert_eq!(
+            insert_values_sql(
+                "t",
+                &columns,
+                &[&row],
+                &col_types,
+                Database::Sqlite,
+                "",
+                true
+            ),
+            "INSERT INTO \"t\" (\"x); DROP TABLE y;--\", \"a`b\") VALUES (NULL, NULL);"
+        );
+    }
+}
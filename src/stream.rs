@@ -0,0 +1,191 @@
+use crate::{Error, Result, utils::check_sheet};
+use calamine::{Cell, Data, Dimensions, Range, Sheets};
+use std::io::{Read, Seek};
+
+type NextCell<'a> = Box<dyn FnMut() -> Result<Option<Cell<Data>>> + Send + 'a>;
+
+/// Pulls a worksheet row by row straight from the file instead of loading it
+/// into a `Range`. Only xlsx and xlsb expose a lazy cell reader.
+pub(crate) struct RowStream<'a> {
+    next_cell: NextCell<'a>,
+    start_col: u32,
+    width: usize,
+    bounds: Option<Dimensions>,
+    row: Option<u32>,
+    first_row: Option<u32>,
+    pending: Option<Cell<Data>>,
+    done: bool,
+    // Merged regions with the value of their top-left cell once it is read.
+    merged: Vec<(Dimensions, Option<Data>)>,
+}
+
+impl<'a> RowStream<'a> {
+    pub(crate) fn open<RS: Read + Seek + Send>(
+        workbook: &'a mut Sheets<RS>,
+        sheet: &str,
+        bounds: Option<Dimensions>,
+        merged: Vec<Dimensions>,
+    ) -> Result<Self> {
+        check_sheet(workbook, sheet)?;
+        let (next_cell, dimensions): (NextCell<'a>, _) = match workbook {
+            Sheets::Xlsx(workbook) => {
+                let mut reader = workbook
+                    .worksheet_cells_reader(sheet)
+                    .map_err(calamine::Error::from)?;
+                let dimensions = reader.dimensions();
+                let next_cell = move || {
+                    let cell = reader.next_cell().map_err(calamine::Error::from)?;
+                    Ok(cell.map(|c| Cell::new(c.get_position(), c.get_value().clone().into())))
+                };
+                (Box::new(next_cell), dimensions)
+            }
+            Sheets::Xlsb(workbook) => {
+                let mut reader = workbook
+                    .worksheet_cells_reader(sheet)
+                    .map_err(calamine::Error::from)?;
+                let dimensions = reader.dimensions();
+                let next_cell = move || {
+                    let cell = reader.next_cell().map_err(calamine::Error::from)?;
+                    Ok(cell.map(|c| Cell::new(c.get_position(), c.get_value().clone().into())))
+                };
+                (Box::new(next_cell), dimensions)
+            }
+            _ => {
+                return Err(Error::ExcelConfigError(
+                    "Streaming is only supported for xlsx and xlsb files".to_owned(),
+                ));
+            }
+        };
+
+        let dimensions = bounds.unwrap_or(dimensions);
+        Ok(Self {
+            next_cell,
+            start_col: dimensions.start.1,
+            width: (dimensions.end.1.saturating_sub(dimensions.start.1) + 1) as usize,
+            bounds,
+            row: bounds.map(|bounds| bounds.start.0),
+            first_row: None,
+            pending: None,
+            done: false,
+            merged: merged.into_iter().map(|region| (region, None)).collect(),
+        })
+    }
+
+    pub(crate) fn next_row(&mut self) -> Result<Option<Vec<Data>>> {
+        let first = match self.pending.take() {
+            Some(cell) => cell,
+            None => match self.pull()? {
+                Some(cell) => cell,
+                None => return Ok(None),
+            },
+        };
+        let row = self.row.unwrap_or(first.get_position().0);
+        self.row = Some(row + 1);
+        self.first_row.get_or_insert(row);
+
+        let mut values = vec![Data::Empty; self.width];
+        // Rows without any cell are absent from the file but present in a `Range`.
+        if first.get_position().0 > row {
+            self.pending = Some(first);
+        } else {
+            self.place(&mut values, first);
+            while let Some(cell) = self.pull()? {
+                if cell.get_position().0 != row {
+                    self.pending = Some(cell);
+                    break;
+                }
+                self.place(&mut values, cell);
+            }
+        }
+        self.fill_merged(row, &mut values);
+
+        Ok(Some(values))
+    }
+
+    // The underlying readers error out when polled past the end of the sheet.
+    fn pull(&mut self) -> Result<Option<Cell<Data>>> {
+        if self.done {
+            return Ok(None);
+        }
+        loop {
+            let cell = (self.next_cell)()?;
+            let Some(bounds) = self.bounds else {
+                self.done = cell.is_none();
+                return Ok(cell);
+            };
+            match cell {
+                // Cells come in row order, so nothing past the last row matters.
+                Some(cell) if cell.get_position().0 > bounds.end.0 => {
+                    self.done = true;
+                    return Ok(None);
+                }
+                Some(cell) if !bounds.contains(cell.get_position().0, cell.get_position().1) => {}
+                cell => {
+                    self.done = cell.is_none();
+                    return Ok(cell);
+                }
+            }
+        }
+    }
+
+    fn fill_merged(&mut self, row: u32, values: &mut [Data]) {
+        let start_col = self.start_col;
+        let index = |col: u32| col.checked_sub(start_col).map(|col| col as usize);
+        for (region, value) in &mut self.merged {
+            if row < region.start.0 || row > region.end.0 {
+                continue;
+            }
+            if row == region.start.0 {
+                *value = index(region.start.1)
+                    .and_then(|col| values.get(col))
+                    .cloned();
+            }
+            let Some(value) = value else {
+                continue;
+            };
+            for col in region.start.1..=region.end.1 {
+                if let Some(cell) = index(col).and_then(|col| values.get_mut(col)) {
+                    *cell = value.clone();
+                }
+            }
+        }
+    }
+
+    fn place(&mut self, values: &mut Vec<Data>, cell: Cell<Data>) {
+        let (_, col) = cell.get_position();
+        if col < self.start_col {
+            return;
+        }
+        let col = (col - self.start_col) as usize;
+        if col >= self.width {
+            self.width = col + 1;
+            values.resize(self.width, Data::Empty);
+        }
+        values[col] = cell.get_value().clone();
+    }
+
+    /// Sheet row of the first row read and column of the first value of
+    /// each row.
+    pub(crate) fn start(&self) -> (u32, u32) {
+        (self.first_row.unwrap_or(0), self.start_col)
+    }
+}
+
+/// Builds a range of `rows` whose first cell is at sheet position `start`.
+pub(crate) fn range_from_rows(rows: &[Vec<Data>], start: (u32, u32)) -> Range<Data> {
+    let (start_row, start_col) = start;
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        return Range::empty();
+    }
+
+    let end_col = start_col + width as u32 - 1;
+    let end_row = start_row + rows.len() as u32 - 1;
+    let mut range = Range::new((start_row, start_col), (end_row, end_col));
+    for (i, row) in rows.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            range.set_value((start_row + i as u32, start_col + j as u32), value.clone());
+        }
+    }
+    range
+}
@@ -1,49 +1,2156 @@
-use crate::{Error, Result, config::ExcelConfig};
-use calamine::{Data, DataType, Range};
+use crate::{
+    Error, Result,
+    comments::header_comments,
+    config::{BoolStrings, ExcelConfig, IdentifierCase},
+    hidden::hidden_columns,
+    log::debug,
+};
+use calamine::{
+    Data, DataType, Dimensions, ExcelDateTime, ExcelDateTimeType, Ods, Range, Reader, Sheets, Xls,
+    XlsOptions, Xlsb, Xlsx,
+};
+use encoding_rs::Encoding;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom},
+    ops::RangeInclusive,
+    path::Path,
+    sync::Arc,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ExcelDataType {
+    /// 64-bit integer: `BIGINT`, or `INTEGER` on SQLite which is 64-bit too.
     Int,
     Float,
     String,
     Bool,
+    Date,
+    Time,
     DateTime,
+    /// Elapsed time such as `[h]:mm`: `INTERVAL` on Postgres, `TIME` on MySQL.
+    Duration,
+    /// Exact numeric with `precision` digits, `scale` of them after the
+    /// decimal point. Never inferred, only set through a type override.
+    Decimal {
+        precision: u8,
+        scale: u8,
+    },
     NULL,
 }
 
-pub fn read_header(
+impl std::str::FromStr for ExcelDataType {
+    type Err = Error;
+
+    /// Parses a type name such as `int`, `text` or `decimal(10,2)`, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        let data_type = match name.as_str() {
+            "int" | "integer" => ExcelDataType::Int,
+            "float" | "double" => ExcelDataType::Float,
+            "string" | "text" => ExcelDataType::String,
+            "bool" | "boolean" => ExcelDataType::Bool,
+            "date" => ExcelDataType::Date,
+            "time" => ExcelDataType::Time,
+            "datetime" | "timestamp" => ExcelDataType::DateTime,
+            "duration" | "interval" => ExcelDataType::Duration,
+            _ => {
+                let Some((precision, scale)) = name
+                    .strip_prefix("decimal(")
+                    .and_then(|args| args.strip_suffix(')'))
+                    .and_then(|args| args.split_once(','))
+                else {
+                    return Err(Error::ExcelConfigError(format!(
+                        "Unknown column type `{}`",
+                        s.trim()
+                    )));
+                };
+                match (precision.trim().parse(), scale.trim().parse()) {
+                    (Ok(precision), Ok(scale)) if precision > 0 && scale <= precision => {
+                        ExcelDataType::Decimal { precision, scale }
+                    }
+                    _ => {
+                        return Err(Error::ExcelConfigError(format!(
+                            "Invalid decimal type `{}`",
+                            s.trim()
+                        )));
+                    }
+                }
+            }
+        };
+        Ok(data_type)
+    }
+}
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+/// Opens an xlsx, xlsm, xlsb, xls or ods workbook, checking the extension and
+/// the file signature first so other files get a clear error. Strings of an
+/// .xls file are decoded with `xls_encoding` if given, instead of the code
+/// page the file declares.
+pub(crate) fn open_workbook(
+    path: &Path,
+    xls_encoding: Option<&'static Encoding>,
+) -> Result<Sheets<WorkbookReader>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let magic = match extension.as_str() {
+        "xlsx" | "xlsm" | "xlam" | "xlsb" | "ods" => ZIP_MAGIC,
+        "xls" | "xla" => OLE_MAGIC,
+        "" => {
+            return Err(Error::ExcelConfigError(format!(
+                "Unsupported file format: {} has no extension",
+                path.display()
+            )));
+        }
+        _ => {
+            return Err(Error::ExcelConfigError(format!(
+                "Unsupported file format: .{}",
+                extension
+            )));
+        }
+    };
+
+    let file = File::open(path).map_err(|e| {
+        Error::ExcelConfigError(match e.kind() {
+            ErrorKind::NotFound => format!("File not found: {}", path.display()),
+            ErrorKind::PermissionDenied => format!("Permission denied: {}", path.display()),
+            _ => format!("Cannot open {}: {}", path.display(), e),
+        })
+    })?;
+    if !file.metadata()?.is_file() {
+        return Err(Error::ExcelConfigError(format!(
+            "Not a file: {}",
+            path.display()
+        )));
+    }
+    let mut signature = Vec::with_capacity(magic.len());
+    file.take(magic.len() as u64).read_to_end(&mut signature)?;
+    if signature != magic {
+        return Err(Error::ExcelConfigError(format!(
+            "{} is not a valid .{} file",
+            path.display(),
+            extension
+        )));
+    }
+
+    let reader = WorkbookReader::File(BufReader::new(File::open(path)?));
+    open_format(reader, &extension, xls_encoding)
+}
+
+/// Like [`open_workbook`] for a workbook held in memory, telling the format
+/// from its content.
+pub(crate) fn open_workbook_bytes(
+    bytes: &WorkbookBytes,
+    xls_encoding: Option<&'static Encoding>,
+) -> Result<Sheets<WorkbookReader>> {
+    let reader = || WorkbookReader::Bytes(Cursor::new(bytes.0.clone()));
+    let formats: &[&str] = if bytes.0.starts_with(OLE_MAGIC) {
+        &["xls"]
+    } else if bytes.0.starts_with(ZIP_MAGIC) {
+        &["xlsx", "xlsb", "ods"]
+    } else {
+        &[]
+    };
+    formats
+        .iter()
+        .find_map(|format| open_format(reader(), format, xls_encoding).ok())
+        .ok_or_else(|| {
+            Error::ExcelConfigError(
+                "The workbook bytes are not an xlsx, xlsb, xls or ods file".to_owned(),
+            )
+        })
+}
+
+/// Opens the workbook of `config`, from its bytes or else from its path.
+pub(crate) fn open_config_workbook(config: &ExcelConfig) -> Result<Sheets<WorkbookReader>> {
+    match config.bytes() {
+        Some(bytes) => open_workbook_bytes(bytes, config.xls_encoding()),
+        None => open_workbook(config.path(), config.xls_encoding()),
+    }
+}
+
+// Opens `reader` as the workbook format of the file extension `format`.
+fn open_format(
+    reader: WorkbookReader,
+    format: &str,
+    xls_encoding: Option<&'static Encoding>,
+) -> Result<Sheets<WorkbookReader>> {
+    let sheets = match format {
+        "xls" | "xla" => {
+            let mut options = XlsOptions::default();
+            options.force_codepage = xls_encoding.and_then(codepage::from_encoding);
+            Sheets::Xls(Xls::new_with_options(reader, options).map_err(calamine::Error::Xls)?)
+        }
+        "xlsb" => Sheets::Xlsb(Xlsb::new(reader).map_err(calamine::Error::Xlsb)?),
+        "ods" => Sheets::Ods(Ods::new(reader).map_err(calamine::Error::Ods)?),
+        _ => Sheets::Xlsx(Xlsx::new(reader).map_err(calamine::Error::Xlsx)?),
+    };
+    Ok(sheets)
+}
+
+/// Contents of a workbook file, shared by the clones of its config.
+#[derive(Clone)]
+pub(crate) struct WorkbookBytes(pub(crate) Arc<[u8]>);
+
+impl std::fmt::Debug for WorkbookBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
+}
+
+/// Reads a workbook from a file or from memory.
+pub(crate) enum WorkbookReader {
+    File(BufReader<File>),
+    Bytes(Cursor<Arc<[u8]>>),
+}
+
+impl Read for WorkbookReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            WorkbookReader::File(reader) => reader.read(buf),
+            WorkbookReader::Bytes(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for WorkbookReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            WorkbookReader::File(reader) => reader.seek(pos),
+            WorkbookReader::Bytes(reader) => reader.seek(pos),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ColumnSchema {
+    pub name: String,
+    pub data_type: ExcelDataType,
+    pub nullable: bool,
+    pub is_primary_key: bool,
+    /// `DEFAULT` of the column, which the database stores for its empty cells.
+    pub default: Option<String>,
+    /// Table and column the column references as a foreign key.
+    pub references: Option<(String, String)>,
+    /// Note on the header cell, stored as the column's comment.
+    pub comment: Option<String>,
+}
+
+impl ColumnSchema {
+    /// The default as a cell of the column's type, `None` without a default
+    /// or if it does not parse as that type.
+    pub(crate) fn default_cell(&self) -> Option<Data> {
+        let cell = Data::String(self.default.clone()?);
+        if fits_type(&cell, self.data_type) {
+            Some(cell)
+        } else {
+            coerce(&cell, self.data_type)
+        }
+    }
+}
+
+/// Columns of the target table, in sheet order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TableSchema {
+    pub columns: Vec<ColumnSchema>,
+}
+
+impl TableSchema {
+    pub fn names(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.name.clone()).collect()
+    }
+
+    pub fn data_types(&self) -> Vec<ExcelDataType> {
+        self.columns.iter().map(|c| c.data_type).collect()
+    }
+
+    pub fn primary_key(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|c| c.is_primary_key)
+            .map(|c| c.name.clone())
+            .collect()
+    }
+}
+
+/// Cuts `sheet` down to the configured cell range, if any. Trailing rows of
+/// the range that hold no cell are dropped, as they are when streaming.
+pub(crate) fn cell_range(sheet: Range<Data>, range: Option<Dimensions>) -> Range<Data> {
+    let Some(range) = range else {
+        return sheet;
+    };
+    let (start_row, start_col) = sheet.start().unwrap_or_default();
+    let last_row = sheet
+        .used_cells()
+        .map(|(row, col, _)| (start_row + row as u32, start_col + col as u32))
+        .filter(|&(row, col)| range.contains(row, col))
+        .map(|(row, _)| row)
+        .max();
+    match last_row {
+        Some(last_row) => sheet.range(range.start, (last_row, range.end.1)),
+        None => Range::empty(),
+    }
+}
+
+/// Reads a worksheet cut down to `range`, optionally with its merged regions
+/// filled in first.
+pub(crate) fn sheet_range<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    sheet: &str,
+    range: Option<Dimensions>,
+    fill_merged_cells: bool,
+) -> Result<Range<Data>> {
+    check_sheet(workbook, sheet)?;
+    let mut cells = workbook.worksheet_range(sheet)?;
+    if fill_merged_cells {
+        fill_merged(&mut cells, &merged_regions(workbook, sheet)?);
+    }
+    Ok(cell_range(cells, range))
+}
+
+/// Fails with the available sheet names if `sheet` doesn't exist, rather
+/// than with calamine's bare error.
+pub(crate) fn check_sheet<RS: Read + Seek>(workbook: &Sheets<RS>, sheet: &str) -> Result<()> {
+    let sheets = workbook.sheet_names();
+    if sheets.iter().any(|name| name == sheet) {
+        return Ok(());
+    }
+    Err(Error::ExcelConfigError(format!(
+        "Sheet `{}` not found; available: {}",
+        sheet,
+        sheets.join(", ")
+    )))
+}
+
+/// Reads the configured sheet the way [`sheet_range`] does, cut off after
+/// the configured max rows and with its data cells cleaned by [`clean_row`].
+pub(crate) fn read_sheet<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    config: &ExcelConfig,
+) -> Result<Range<Data>> {
+    let sheet = sheet_range(
+        workbook,
+        config.sheet(),
+        config.range(),
+        config.fill_merged_cells(),
+    )?;
+    Ok(prepare_sheet(sheet, config))
+}
+
+/// Cuts `sheet` off after the configured max rows and cleans its data cells
+/// by [`clean_row`].
+pub(crate) fn prepare_sheet(mut sheet: Range<Data>, config: &ExcelConfig) -> Range<Data> {
+    if let Some(max_rows) = config.max_rows()
+        && let (Some(start), Some(end)) = (sheet.start(), sheet.end())
+    {
+        let last_row = start.0 as usize + max_rows + config.data_start_row() - 2;
+        if last_row < end.0 as usize {
+            sheet = sheet.range(start, (last_row as u32, end.1));
+        }
+    }
+    if !config.null_values().is_empty() || config.durations_as_seconds() || config.all_text() {
+        let start = sheet.start().unwrap_or_default();
+        let cleaned = sheet
+            .used_cells()
+            .filter(|&(row, _, _)| row + 1 >= config.data_start_row())
+            .filter_map(|(row, col, data)| {
+                let position = (start.0 + row as u32, start.1 + col as u32);
+                clean_cell(data, config).map(|data| (position, data))
+            })
+            .collect::<Vec<_>>();
+        for (position, data) in cleaned {
+            sheet.set_value(position, data);
+        }
+    }
+    sheet
+}
+
+/// Empties the cells of a data row that match the configured NULL values and
+/// turns durations into seconds if configured.
+pub(crate) fn clean_row(row: &mut [Data], config: &ExcelConfig) {
+    for cell in row {
+        if let Some(data) = clean_cell(cell, config) {
+            *cell = data;
+        }
+    }
+}
+
+fn clean_cell(data: &Data, config: &ExcelConfig) -> Option<Data> {
+    if is_null_value(data, config) {
+        return Some(Data::Empty);
+    }
+    let seconds = config
+        .durations_as_seconds()
+        .then(|| duration(data))
+        .flatten()
+        .map(|d| Data::Float(d.num_milliseconds() as f64 / 1000.0));
+    if config.all_text() {
+        return cell_text(seconds.as_ref().unwrap_or(data))
+            .map(Data::String)
+            .or(seconds);
+    }
+    seconds
+}
+
+// Text of a non-text cell for the all text mode.
+fn cell_text(data: &Data) -> Option<String> {
+    match data {
+        Data::Int(v) => Some(v.to_string()),
+        Data::Float(v) => Some(v.to_string()),
+        Data::Bool(v) => Some(v.to_string()),
+        Data::DateTimeIso(s) | Data::DurationIso(s) => Some(s.clone()),
+        Data::DateTime(_) => match infer_type(data) {
+            ExcelDataType::Date => date_string(data),
+            ExcelDataType::Time => time_string(data),
+            ExcelDataType::Duration => duration_string(data),
+            _ => datetime_string(data),
+        },
+        Data::String(_) | Data::Empty | Data::Error(_) => None,
+    }
+}
+
+fn is_null_value(data: &Data, config: &ExcelConfig) -> bool {
+    let Some(text) = data.get_string() else {
+        return false;
+    };
+    let text = text.trim();
+    config.null_values().iter().any(|value| {
+        if config.null_values_ignore_case() {
+            value.eq_ignore_ascii_case(text)
+        } else {
+            value == text
+        }
+    })
+}
+
+pub(crate) fn merged_regions<RS: Read + Seek>(
+    workbook: &mut Sheets<RS>,
+    sheet: &str,
+) -> Result<Vec<Dimensions>> {
+    match workbook {
+        Sheets::Xlsx(workbook) => Ok(workbook
+            .worksheet_merge_cells(sheet)
+            .transpose()
+            .map_err(calamine::Error::from)?
+            .unwrap_or_default()),
+        Sheets::Xls(workbook) => Ok(workbook.worksheet_merge_cells(sheet).unwrap_or_default()),
+        _ => Err(Error::ExcelConfigError(
+            "Merged cells can only be filled for xlsx and xls files".to_owned(),
+        )),
+    }
+}
+
+/// Copies the top-left value of every merged region across the region.
+pub(crate) fn fill_merged(sheet: &mut Range<Data>, regions: &[Dimensions]) {
+    for region in regions {
+        let Some(value) = sheet.get_value(region.start).cloned() else {
+            continue;
+        };
+        for row in region.start.0..=region.end.0 {
+            for col in region.start.1..=region.end.1 {
+                sheet.set_value((row, col), value.clone());
+            }
+        }
+    }
+}
+
+/// `col1` to `col{width}`, for sheets without a header row.
+pub(crate) fn generated_header(width: usize) -> Vec<String> {
+    (1..=width).map(|i| format!("col{}", i)).collect()
+}
+
+/// Strips a `:type` hint such as `amount:float` from each header, returning
+/// the bare names and the hinted types.
+pub(crate) fn split_type_hints(
+    header: Vec<String>,
+) -> Result<(Vec<String>, Vec<Option<ExcelDataType>>)> {
+    let columns = header
+        .into_iter()
+        .map(|name| {
+            let Some((bare, hint)) = name.rsplit_once(':') else {
+                return Ok((name, None));
+            };
+            match hint.parse() {
+                Ok(data_type) => Ok((bare.trim_end().to_owned(), Some(data_type))),
+                Err(Error::ExcelConfigError(message)) => Err(Error::ExcelConfigError(format!(
+                    "{} in header `{}`",
+                    message, name
+                ))),
+                Err(e) => Err(e),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(columns.into_iter().unzip())
+}
+
+/// Cells of the 1-based header `rows` as strings, joined per column with
+/// `separator`. Empty cells of all but the last row take the value to their
+/// left, as for a category spanning several columns.
+pub(crate) fn header_row(
+    sheet: &Range<Data>,
+    rows: RangeInclusive<usize>,
+    separator: &str,
+) -> Option<Vec<String>> {
+    let mut rows = sheet
+        .rows()
+        .skip(rows.start() - 1)
+        .take(rows.count())
+        .map(|row| row.iter().map(ToString::to_string).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let last = rows.pop()?;
+    for row in &mut rows {
+        for i in 1..row.len() {
+            if row[i].is_empty() {
+                row[i] = row[i - 1].clone();
+            }
+        }
+    }
+    let header = (0..last.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].as_str())
+                .chain([last[i].as_str()])
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+                .join(separator)
+        })
+        .collect();
+    Some(header)
+}
+
+pub fn read_header(config: &ExcelConfig, sheet: &Range<Data>) -> Result<TableSchema> {
+    read_schema(config, sheet).map(|(schema, _)| schema)
+}
+
+/// Like [`read_header`], also returning the positions of the sheet columns
+/// that make it into the table when some are excluded.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(sheet = config.sheet()))
+)]
+pub(crate) fn read_schema(
     config: &ExcelConfig,
     sheet: &Range<Data>,
-) -> Result<(Vec<String>, Vec<ExcelDataType>)> {
-    let header = match (config.headers(), sheet.headers()) {
-        (Some(h), Some(_)) => h.clone(),
-        (Some(h), None) => h.clone(),
-        (None, Some(h)) => h,
+) -> Result<(TableSchema, Option<Vec<usize>>)> {
+    let (header, hints) = match (
+        config.headers(),
+        header_row(sheet, config.header_rows(), config.header_separator()),
+    ) {
+        _ if config.no_header() => (generated_header(sheet.width()), Vec::new()),
+        (Some(h), Some(_)) => (h.clone(), Vec::new()),
+        (Some(h), None) => (h.clone(), Vec::new()),
+        (None, Some(h)) if config.header_type_hints() => split_type_hints(h)?,
+        (None, Some(h)) => (h, Vec::new()),
         (None, None) => return Err(Error::ExcelConfigError("No header found".to_string())),
     };
 
-    let first_row = sheet
-        .rows()
-        .nth(config.data_start_row() - 1)
-        .ok_or(Error::ExcelConfigError("No data found".to_owned()))?;
+    if sheet.rows().nth(config.data_start_row() - 1).is_none() {
+        return Err(Error::ExcelConfigError("No data found".to_owned()));
+    }
+
+    let col_types = infer_column_types(
+        sheet,
+        config.data_start_row(),
+        config.infer_rows(),
+        config.coerce_numeric_strings(),
+    )
+    .into_iter()
+    .enumerate()
+    .map(|(i, data_type)| {
+        header
+            .get(i)
+            .and_then(|name| config.column_types().get(name))
+            .copied()
+            .or(hints.get(i).copied().flatten())
+            .unwrap_or(data_type)
+    })
+    .collect::<Vec<_>>();
+    if col_types.len() != header.len() {
+        return Err(Error::ExcelConfigError(format!(
+            "Found {} headers for {} data columns",
+            header.len(),
+            col_types.len()
+        )));
+    }
+    let nullable = if config.not_null() {
+        infer_nullable(
+            sheet,
+            config.data_start_row(),
+            &col_types,
+            config.empty_string_as_null(),
+            config.bool_strings(),
+        )
+    } else {
+        vec![true; col_types.len()]
+    };
+
+    // A range given to `load_range` has no file to read these from.
+    let has_file = config.bytes().is_some() || !config.path().as_os_str().is_empty();
+    let (start_row, start_col) = sheet.start().unwrap_or((0, 0));
+    let hidden = if config.skip_hidden_columns() && has_file {
+        hidden_columns(config.path(), config.bytes(), config.sheet(), start_col)?
+    } else {
+        Vec::new()
+    };
+    let comments = if config.column_comments() && has_file && !config.no_header() {
+        let header_rows = config.header_rows();
+        let rows =
+            start_row + *header_rows.start() as u32 - 1..=start_row + *header_rows.end() as u32 - 1;
+        let mut comments = header_comments(
+            config.path(),
+            config.bytes(),
+            config.sheet(),
+            rows,
+            start_col,
+        )?;
+        (0..header.len()).map(|i| comments.remove(&i)).collect()
+    } else {
+        vec![None; header.len()]
+    };
+    let (header, col_types, nullable, comments, selection) =
+        if config.exclude_columns().is_empty() && !hidden.iter().any(|&i| i < header.len()) {
+            (header, col_types, nullable, comments, None)
+        } else {
+            let missing = config
+                .exclude_columns()
+                .iter()
+                .filter(|name| !header.contains(name))
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Err(Error::ExcelConfigError(format!(
+                    "Excluded column not found in headers: {}",
+                    missing.join(", ")
+                )));
+            }
+            let selection = (0..header.len())
+                .filter(|&i| !hidden.contains(&i) && !config.exclude_columns().contains(&header[i]))
+                .collect::<Vec<_>>();
+            let select = |i: &usize| selection.contains(i);
+            (
+                keep(header, select),
+                keep(col_types, select),
+                keep(nullable, select),
+                keep(comments, select),
+                Some(selection),
+            )
+        };
+
+    let header = rename_header(
+        header,
+        config.rename_columns(),
+        config.sanitize_columns(),
+        config.identifier_case(),
+    );
+    let header = if config.dedup_headers() {
+        dedup_names(header)
+    } else {
+        check_duplicates(&header)?;
+        header
+    };
+    config.check_columns(&header)?;
+    if let Some(id_column) = config.id_column()
+        && header
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(id_column))
+    {
+        return Err(Error::ExcelConfigError(format!(
+            "The id column `{}` is already a header",
+            id_column
+        )));
+    }
+    if let Some(loaded_at_column) = config.loaded_at_column()
+        && header
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(loaded_at_column))
+    {
+        return Err(Error::ExcelConfigError(format!(
+            "The loaded at column `{}` is already a header",
+            loaded_at_column
+        )));
+    }
+
+    let columns = header
+        .into_iter()
+        .zip(col_types)
+        .zip(nullable)
+        .zip(comments)
+        .map(|(((name, data_type), nullable), comment)| ColumnSchema {
+            is_primary_key: config.primary_key().contains(&name),
+            default: config.column_defaults().get(&name).cloned(),
+            references: config.foreign_keys().get(&name).cloned(),
+            comment,
+            name,
+            data_type,
+            nullable,
+        })
+        .collect::<Vec<_>>();
+    if let Some(column) = columns
+        .iter()
+        .find(|column| column.default.is_some() && column.default_cell().is_none())
+    {
+        return Err(Error::ExcelConfigError(format!(
+            "Invalid default `{}` for the {:?} column `{}`",
+            column.default.as_deref().unwrap_or_default(),
+            column.data_type,
+            column.name
+        )));
+    }
+    let schema = TableSchema { columns };
+    debug!("read columns {:?}", schema.names());
+    Ok((schema, selection))
+}
+
+fn keep<T>(values: Vec<T>, select: impl Fn(&usize) -> bool) -> Vec<T> {
+    values
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| select(i))
+        .map(|(_, value)| value)
+        .collect()
+}
+
+/// Picks the `selection` columns out of a sheet row, see [`read_schema`].
+pub(crate) fn select_columns<'a>(row: &'a [Data], selection: Option<&[usize]>) -> Cow<'a, [Data]> {
+    match selection {
+        Some(selection) => selection
+            .iter()
+            .map(|&i| row.get(i).cloned().unwrap_or_default())
+            .collect(),
+        None => Cow::Borrowed(row),
+    }
+}
+
+/// Whether every cell of `row` is empty or an error.
+pub(crate) fn is_blank_row(row: &[Data], empty_string_as_null: bool) -> bool {
+    row.iter()
+        .all(|cell| is_null(cell, empty_string_as_null) || matches!(cell, Data::Error(_)))
+}
+
+/// Turns headers into plain SQL column names: lowercase, runs of anything
+/// but ASCII letters and digits replaced by `_`, and duplicates suffixed with
+/// `_2`, `_3` and so on.
+pub fn sanitize_column_names(headers: &[String]) -> Vec<String> {
+    dedup_names(
+        headers
+            .iter()
+            .map(|name| sanitize_column_name(name))
+            .collect(),
+    )
+}
+
+pub(crate) fn rename_header(
+    header: Vec<String>,
+    rename: &HashMap<String, String>,
+    sanitize: bool,
+    case: IdentifierCase,
+) -> Vec<String> {
+    let header = header
+        .into_iter()
+        .map(|name| {
+            let name = match rename.get(&name) {
+                Some(renamed) => renamed.clone(),
+                None if sanitize => sanitize_column_name(&name),
+                None => name,
+            };
+            case.apply(&name)
+        })
+        .collect();
+    if sanitize {
+        dedup_names(header)
+    } else {
+        header
+    }
+}
+
+fn check_duplicates(header: &[String]) -> Result<()> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for name in header {
+        if !seen.insert(name.to_lowercase()) && !duplicates.contains(&name.as_str()) {
+            duplicates.push(name.as_str());
+        }
+    }
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ExcelConfigError(format!(
+            "Duplicate column names: {}",
+            duplicates.join(", ")
+        )))
+    }
+}
+
+fn sanitize_column_name(name: &str) -> String {
+    let mut sanitized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            sanitized.push(c.to_ascii_lowercase());
+        } else if !sanitized.is_empty() && !sanitized.ends_with('_') {
+            sanitized.push('_');
+        }
+    }
+    let sanitized = sanitized.trim_end_matches('_');
+    if sanitized.is_empty() {
+        "column".to_owned()
+    } else {
+        sanitized.to_owned()
+    }
+}
+
+// Column names are compared ignoring case since MySQL does.
+pub(crate) fn dedup_names(names: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let mut unique = name.clone();
+            let mut suffix = 2;
+            while !seen.insert(unique.to_lowercase()) {
+                unique = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+fn infer_column_types(
+    sheet: &Range<Data>,
+    data_start_row: usize,
+    infer_rows: usize,
+    numeric_strings: bool,
+) -> Vec<ExcelDataType> {
+    scan_columns(
+        sheet.rows().skip(data_start_row - 1).take(infer_rows),
+        vec![ExcelDataType::NULL; sheet.width()],
+        |col_types, row| {
+            for (col_type, data) in col_types.iter_mut().zip(row) {
+                let data_type = match infer_type(data) {
+                    ExcelDataType::String if numeric_strings => numeric_string_type(data),
+                    data_type => data_type,
+                };
+                *col_type = promote(*col_type, data_type);
+            }
+        },
+        promote,
+    )
+}
+
+// Unlike the types, nullability is checked on every row: a single empty cell
+// past the sample would make the whole load fail. Cells not fitting the type
+// may be stored as NULL depending on the load's type error policy.
+fn infer_nullable(
+    sheet: &Range<Data>,
+    data_start_row: usize,
+    col_types: &[ExcelDataType],
+    empty_string_as_null: bool,
+    bool_strings: &BoolStrings,
+) -> Vec<bool> {
+    scan_columns(
+        sheet.rows().skip(data_start_row - 1),
+        vec![false; col_types.len()],
+        |nullable, row| {
+            let row = parse_bool_strings(Cow::Borrowed(row), col_types, bool_strings);
+            for ((nullable, data), data_type) in nullable.iter_mut().zip(row.iter()).zip(col_types)
+            {
+                *nullable |= is_null(data, empty_string_as_null) || !fits_type(data, *data_type);
+            }
+        },
+        |a, b| a || b,
+    )
+}
+
+// Folds `rows` into one value per column with `step`, starting from `init`.
+#[cfg(not(feature = "rayon"))]
+fn scan_columns<'r, T: Clone + Send + Sync>(
+    rows: impl Iterator<Item = &'r [Data]>,
+    init: Vec<T>,
+    step: impl Fn(&mut [T], &[Data]) + Sync,
+    _merge: impl Fn(T, T) -> T + Sync,
+) -> Vec<T> {
+    let mut values = init;
+    for row in rows {
+        step(&mut values, row);
+    }
+    values
+}
+
+// Scans chunks of rows on the rayon pool and combines their values with
+// `merge`. Both merges are associative and commutative, so the result does
+// not depend on how the rows are split between threads.
+#[cfg(feature = "rayon")]
+fn scan_columns<'r, T: Clone + Send + Sync>(
+    rows: impl Iterator<Item = &'r [Data]>,
+    init: Vec<T>,
+    step: impl Fn(&mut [T], &[Data]) + Sync,
+    merge: impl Fn(T, T) -> T + Sync,
+) -> Vec<T> {
+    use rayon::prelude::*;
+
+    let rows = rows.collect::<Vec<_>>();
+    rows.par_chunks(chunk_size(rows.len()))
+        .map(|chunk| {
+            let mut values = init.clone();
+            for row in chunk {
+                step(&mut values, row);
+            }
+            values
+        })
+        .reduce(
+            || init.clone(),
+            |a, b| a.into_iter().zip(b).map(|(a, b)| merge(a, b)).collect(),
+        )
+}
 
-    let col_types = first_row.iter().map(infer_type).collect::<Vec<_>>();
+// One chunk per thread, so that even the default sample of 100 rows is
+// split, but long enough to be worth a task.
+#[cfg(feature = "rayon")]
+fn chunk_size(rows: usize) -> usize {
+    rows.div_ceil(rayon::current_num_threads()).max(32)
+}
 
-    Ok((header, col_types))
+fn promote(current: ExcelDataType, next: ExcelDataType) -> ExcelDataType {
+    match (current, next) {
+        (ExcelDataType::NULL, t) | (t, ExcelDataType::NULL) => t,
+        (a, b) if a == b => a,
+        (ExcelDataType::Int, ExcelDataType::Float) | (ExcelDataType::Float, ExcelDataType::Int) => {
+            ExcelDataType::Float
+        }
+        (
+            ExcelDataType::Date | ExcelDataType::Time | ExcelDataType::DateTime,
+            ExcelDataType::Date | ExcelDataType::Time | ExcelDataType::DateTime,
+        ) => ExcelDataType::DateTime,
+        _ => ExcelDataType::String,
+    }
 }
 
+// xlsx stores every number as a double, xls and xlsb store some whole numbers
+// as integers, so whole doubles count as integers too. A column becomes Float
+// once it holds a fraction.
 fn infer_type(data: &Data) -> ExcelDataType {
-    if data.is_int() {
+    if data.is_int() || matches!(data, Data::Float(v) if is_whole(*v)) {
         ExcelDataType::Int
     } else if data.is_float() {
         ExcelDataType::Float
     } else if data.is_bool() {
         ExcelDataType::Bool
+    } else if data.get_string() == Some("") || data.is_empty() || data.is_error() {
+        ExcelDataType::NULL
     } else if data.is_string() {
         ExcelDataType::String
-    } else if data.is_empty() || data.is_error() {
-        ExcelDataType::NULL
     } else {
-        ExcelDataType::DateTime
+        match data {
+            Data::DateTime(dt) if dt.is_duration() => ExcelDataType::Duration,
+            Data::DurationIso(_) => ExcelDataType::Duration,
+            // Serials below one day carry no date, whole serials no time of day.
+            Data::DateTime(dt) if (0.0..1.0).contains(&dt.as_f64()) => ExcelDataType::Time,
+            Data::DateTime(dt) if dt.as_f64().fract() == 0.0 => ExcelDataType::Date,
+            Data::DateTimeIso(s) if s.len() == 10 && !s.contains('T') => ExcelDataType::Date,
+            Data::DateTimeIso(s) if s.contains(':') && !s.contains('-') => ExcelDataType::Time,
+            _ => ExcelDataType::DateTime,
+        }
+    }
+}
+
+// Int or Float for text holding a plain number, parsed like the values are
+// bound later, String for any other text.
+fn numeric_string_type(data: &Data) -> ExcelDataType {
+    let Some(text) = data.get_string() else {
+        return ExcelDataType::String;
+    };
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let plain = digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'));
+    let code = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !plain || code {
+        ExcelDataType::String
+    } else if data.as_i64().is_some() {
+        ExcelDataType::Int
+    } else if data.as_f64().is_some() {
+        ExcelDataType::Float
+    } else {
+        ExcelDataType::String
+    }
+}
+
+pub(crate) fn is_null(data: &Data, empty_string_as_null: bool) -> bool {
+    data.is_empty() || data.is_error() || (empty_string_as_null && data.get_string() == Some(""))
+}
+
+/// Whether a non-empty cell can be stored in a column of `data_type`.
+pub(crate) fn fits_type(data: &Data, data_type: ExcelDataType) -> bool {
+    match data_type {
+        ExcelDataType::Int => match data {
+            // `as_i64` saturates, so large floats would silently become i64::MAX.
+            Data::Float(value) => is_whole(*value),
+            _ => data.as_i64().is_some(),
+        },
+        ExcelDataType::Float => data.as_f64().is_some(),
+        ExcelDataType::Bool => data.get_bool().is_some(),
+        ExcelDataType::String | ExcelDataType::NULL => data.as_string().is_some(),
+        ExcelDataType::Date => date_string(data).is_some(),
+        ExcelDataType::Time => time_string(data).is_some(),
+        ExcelDataType::DateTime => datetime_string(data).is_some(),
+        ExcelDataType::Duration => duration_string(data).is_some(),
+        ExcelDataType::Decimal { scale, .. } => decimal_string(data, scale).is_some(),
+    }
+}
+
+fn in_i64_range(value: f64) -> bool {
+    (i64::MIN as f64..i64::MAX as f64).contains(&value)
+}
+
+fn is_whole(value: f64) -> bool {
+    value.fract() == 0.0 && in_i64_range(value)
+}
+
+/// Best-effort conversion of a cell that does not fit `data_type`.
+pub(crate) fn coerce(data: &Data, data_type: ExcelDataType) -> Option<Data> {
+    let text = data.as_string()?;
+    let number = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',' && *c != '_')
+        .collect::<String>();
+    match data_type {
+        ExcelDataType::Int => match number.parse::<i64>() {
+            Ok(value) => Some(Data::Int(value)),
+            Err(_) => number
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.fract() == 0.0 && in_i64_range(*value))
+                .map(|value| Data::Int(value as i64)),
+        },
+        ExcelDataType::Float => number.parse::<f64>().ok().map(Data::Float),
+        ExcelDataType::Decimal { .. } => is_decimal(&number).then_some(Data::String(number)),
+        ExcelDataType::Bool => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(Data::Bool(true)),
+            "false" | "no" | "n" | "0" => Some(Data::Bool(false)),
+            _ => None,
+        },
+        ExcelDataType::String
+        | ExcelDataType::Date
+        | ExcelDataType::Time
+        | ExcelDataType::DateTime
+        | ExcelDataType::Duration
+        | ExcelDataType::NULL => None,
+    }
+}
+
+/// Turns text cells of boolean columns that `bool_strings` recognizes into
+/// real booleans, copying the row only if a cell changes.
+pub(crate) fn parse_bool_strings<'a>(
+    mut row: Cow<'a, [Data]>,
+    col_types: &[ExcelDataType],
+    bool_strings: &BoolStrings,
+) -> Cow<'a, [Data]> {
+    for (i, data_type) in col_types.iter().enumerate() {
+        if *data_type != ExcelDataType::Bool {
+            continue;
+        }
+        if let Some(value) = row
+            .get(i)
+            .and_then(Data::get_string)
+            .and_then(|s| bool_strings.parse(s))
+        {
+            row.to_mut()[i] = Data::Bool(value);
+        }
+    }
+    row
+}
+
+/// Formats a cell for a decimal column without scientific notation. Floats
+/// are rounded to `scale` digits, decimal text is passed through untouched so
+/// no precision is lost.
+pub(crate) fn decimal_string(data: &Data, scale: u8) -> Option<String> {
+    match data {
+        Data::Int(v) => Some(v.to_string()),
+        Data::Float(v) if v.is_finite() => Some(format!("{:.*}", scale as usize, v)),
+        Data::String(s) if is_decimal(s.trim()) => Some(s.trim().to_owned()),
+        _ => None,
+    }
+}
+
+fn is_decimal(text: &str) -> bool {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (int, fract) = digits.split_once('.').unwrap_or((digits, ""));
+    !(int.is_empty() && fract.is_empty())
+        && int.chars().all(|c| c.is_ascii_digit())
+        && fract.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Format a date time cell as an ISO 8601 string.
+///
+/// Serials stored in `Data::DateTime` carry their workbook's 1900/1904 date
+/// system, plain numbers are read as 1900-based serials. Time-only values
+/// land on Excel's day zero, 1899-12-31.
+pub(crate) fn datetime_string(data: &Data) -> Option<String> {
+    format_datetime(data, "%Y-%m-%dT%H:%M:%S%.f")
+}
+
+/// Format a date cell as `YYYY-MM-DD`, dropping any time of day.
+pub(crate) fn date_string(data: &Data) -> Option<String> {
+    format_datetime(data, "%Y-%m-%d")
+}
+
+/// Format a time cell as `HH:MM:SS`, dropping any date.
+pub(crate) fn time_string(data: &Data) -> Option<String> {
+    format_datetime(data, "%H:%M:%S%.f")
+}
+
+/// The elapsed time of a duration cell. Other cells, numbers included, are
+/// not durations.
+pub(crate) fn duration(data: &Data) -> Option<chrono::Duration> {
+    match data {
+        Data::DateTime(dt) if dt.is_duration() => dt.as_duration(),
+        Data::DurationIso(s) => parse_iso_duration(s),
+        _ => None,
+    }
+}
+
+/// Format a duration cell as `[-]H:MM:SS`, with as many hours as needed.
+/// Plain numbers are read as a number of days like Excel does.
+pub(crate) fn duration_string(data: &Data) -> Option<String> {
+    let duration = match data {
+        Data::Int(_) | Data::Float(_) => data
+            .as_f64()
+            .map(|days| chrono::Duration::milliseconds((days * 86_400_000.0).round() as i64)),
+        Data::String(s) => parse_iso_duration(s.trim()).or_else(|| parse_clock_duration(s.trim())),
+        _ => duration(data),
+    }?;
+    let sign = if duration < chrono::Duration::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let millis = duration.num_milliseconds().unsigned_abs();
+    let (seconds, millis) = (millis / 1000, millis % 1000);
+    let mut text = format!(
+        "{}{}:{:02}:{:02}",
+        sign,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if millis > 0 {
+        text.push_str(&format!(".{:03}", millis));
+    }
+    Some(text)
+}
+
+// Parses text like `36:30:15.5` or `-1:05:00`, hours or minutes first.
+fn parse_clock_duration(text: &str) -> Option<chrono::Duration> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let parts = text.split(':').collect::<Vec<_>>();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let number = |part: &str| {
+        (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit() || b == b'.'))
+            .then(|| part.parse::<f64>().ok())
+            .flatten()
+    };
+    let mut millis = 0.0;
+    for (i, part) in parts.iter().enumerate() {
+        let value = number(part)?;
+        // Only the seconds may have a fraction, only the first part may be above 59.
+        if (i + 1 < parts.len() && value.fract() != 0.0) || (i > 0 && value >= 60.0) {
+            return None;
+        }
+        millis = millis * 60.0 + value * 1000.0;
+    }
+    if parts.len() == 2 {
+        millis *= 60.0;
+    }
+    let millis = millis.round() as i64;
+    Some(chrono::Duration::milliseconds(if negative {
+        -millis
+    } else {
+        millis
+    }))
+}
+
+// Parses the `PnDTnHnMnS` form OpenDocument uses for durations.
+fn parse_iso_duration(text: &str) -> Option<chrono::Duration> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let text = text.strip_prefix('P')?;
+    let (date, time) = text.split_once('T').unwrap_or((text, ""));
+    const DATE_UNITS: &[(char, f64)] = &[('D', 86_400_000.0)];
+    const TIME_UNITS: &[(char, f64)] = &[('H', 3_600_000.0), ('M', 60_000.0), ('S', 1000.0)];
+
+    let mut millis = 0.0;
+    for (mut rest, units) in [(date, DATE_UNITS), (time, TIME_UNITS)] {
+        for &(unit, scale) in units {
+            if let Some((value, tail)) = rest.split_once(unit) {
+                millis += value.parse::<f64>().ok()? * scale;
+                rest = tail;
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+    }
+    let millis = millis.round() as i64;
+    let millis = if negative { -millis } else { millis };
+    Some(chrono::Duration::milliseconds(millis))
+}
+
+fn format_datetime(data: &Data, format: &str) -> Option<String> {
+    let datetime = match data {
+        Data::DateTime(dt) => dt.as_datetime(),
+        Data::Int(_) | Data::Float(_) => data
+            .as_f64()
+            .and_then(|v| ExcelDateTime::new(v, ExcelDateTimeType::DateTime, false).as_datetime()),
+        Data::DateTimeIso(s) | Data::String(s) => parse_datetime(s.trim()),
+        _ => None,
+    };
+    datetime.map(|dt| dt.format(format).to_string())
+}
+
+// Parses ISO 8601 text: a date, a time of day or both, the latter optionally
+// with an offset, which is converted to UTC. Times land on Excel's day zero.
+fn parse_datetime(text: &str) -> Option<chrono::NaiveDateTime> {
+    const DATETIME_FORMATS: &[&str] = &[
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+    const TIME_FORMATS: &[&str] = &["%H:%M:%S%.f", "%H:%M"];
+    if let Some(datetime) = DATETIME_FORMATS
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(text, format).ok())
+    {
+        return Some(datetime);
+    }
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Some(datetime.naive_utc());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0);
+    }
+    let time = TIME_FORMATS
+        .iter()
+        .find_map(|format| chrono::NaiveTime::parse_from_str(text, format).ok())?;
+    chrono::NaiveDate::from_ymd_opt(1899, 12, 31).map(|day| day.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_header() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .primary_key(vec!["id".to_owned()])
+            .build()
+            .unwrap();
+        let mut workbook = calamine::open_workbook_auto(path).unwrap();
+        let sheet = calamine::Reader::worksheet_range(&mut workbook, "Sheet1").unwrap();
+
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["id", "name", "score", "active", "when"]);
+        assert_eq!(schema.primary_key(), ["id"]);
+        assert!(!schema.columns[0].nullable);
+        assert!(schema.columns[2].nullable);
+    }
+
+    #[test]
+    fn test_column_types() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .column_types(HashMap::from([
+                ("id".to_owned(), ExcelDataType::Float),
+                ("score".to_owned(), ExcelDataType::String),
+                ("missing".to_owned(), ExcelDataType::Bool),
+            ]))
+            .build()
+            .unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        let types = read_header(&config, &sheet)
+            .unwrap()
+            .columns
+            .iter()
+            .map(|column| column.data_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                ExcelDataType::Float,
+                ExcelDataType::String,
+                ExcelDataType::String,
+                ExcelDataType::Bool,
+                ExcelDataType::DateTime
+            ]
+        );
+    }
+
+    #[test]
+    fn test_null_values() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/nulls.xlsx");
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let builder = crate::config::ExcelConfigBuilder::new(path)
+            .null_values(vec!["NULL".to_owned(), "-".to_owned()]);
+
+        let config = builder.clone().build().unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.columns[1].data_type, ExcelDataType::String);
+        assert_eq!(sheet.get((1, 1)), Some(&Data::Empty));
+        assert_eq!(sheet.get((2, 2)), Some(&Data::Empty));
+
+        let config = builder.null_values_ignore_case(true).build().unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.columns[1].data_type, ExcelDataType::Int);
+        assert!(schema.columns[1].nullable);
+    }
+
+    #[test]
+    fn test_exclude_columns() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut workbook = calamine::open_workbook_auto(path).unwrap();
+        let sheet = calamine::Reader::worksheet_range(&mut workbook, "Sheet1").unwrap();
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .exclude_columns(vec!["name".to_owned(), "active".to_owned()])
+            .build()
+            .unwrap();
+        let (schema, selection) = read_schema(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["id", "score", "when"]);
+        let row = sheet.rows().nth(1).unwrap();
+        let row = select_columns(row, selection.as_deref());
+        assert_eq!(row.len(), 3);
+        assert_eq!(row[1], sheet.rows().nth(1).unwrap()[2]);
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .exclude_columns(vec!["nmae".to_owned()])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            read_header(&config, &sheet),
+            Err(Error::ExcelConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_skip_hidden_columns() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/hidden_columns.xlsx"
+        );
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let builder = crate::config::ExcelConfigBuilder::new(path).skip_hidden_columns(true);
+
+        let config = builder.clone().build().unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        let (schema, selection) = read_schema(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["id", "name", "amount"]);
+        assert_eq!(selection.as_deref(), Some(&[0, 2, 4][..]));
+
+        let config = builder.clone().range("C1:E4").build().unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        assert_eq!(
+            read_header(&config, &sheet).unwrap().names(),
+            ["name", "amount"]
+        );
+
+        let config = builder.primary_key(vec!["secret".to_owned()]).build();
+        assert!(matches!(config, Err(Error::ExcelConfigError(_))));
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .build()
+            .unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        assert_eq!(read_header(&config, &sheet).unwrap().columns.len(), 5);
+    }
+
+    #[test]
+    fn test_column_comments() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/comments.xlsx");
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let builder = crate::config::ExcelConfigBuilder::new(path).column_comments(true);
+        let comments = |config: &ExcelConfig, workbook: &mut Sheets<_>| {
+            let sheet = read_sheet(workbook, config).unwrap();
+            read_header(config, &sheet)
+                .unwrap()
+                .columns
+                .into_iter()
+                .map(|column| column.comment)
+                .collect::<Vec<_>>()
+        };
+
+        let config = builder.clone().build().unwrap();
+        assert_eq!(
+            comments(&config, &mut workbook),
+            [
+                Some("Customer number & key".to_owned()),
+                None,
+                Some("Net amount in EUR".to_owned())
+            ]
+        );
+        let config = builder
+            .exclude_columns(vec!["id".to_owned()])
+            .build()
+            .unwrap();
+        assert_eq!(
+            comments(&config, &mut workbook),
+            [None, Some("Net amount in EUR".to_owned())]
+        );
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .build()
+            .unwrap();
+        assert_eq!(comments(&config, &mut workbook), [None, None, None]);
+    }
+
+    #[test]
+    fn test_header_rows() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/merged.xlsx");
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .header_rows(1..=2)
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 3);
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let sheet = sheet_range(&mut workbook, "Sheet1", None, false).unwrap();
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["category", "item_name", "item_price"]);
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .header_rows(1..=2)
+            .header_separator(" / ")
+            .build()
+            .unwrap();
+        assert_eq!(
+            header_row(&sheet, config.header_rows(), config.header_separator()).unwrap(),
+            ["category", "item / name", "item / price"]
+        );
+    }
+
+    #[test]
+    fn test_no_header() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut sheet = Range::new((0, 0), (1, 2));
+        sheet.set_value((0, 0), Data::Int(1));
+        sheet.set_value((0, 1), Data::String("x".to_owned()));
+        sheet.set_value((1, 0), Data::Int(2));
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .no_header(true)
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 1);
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["col1", "col2", "col3"]);
+        assert_eq!(schema.columns[0].data_type, ExcelDataType::Int);
+
+        let result = crate::config::ExcelConfigBuilder::new(path)
+            .no_header(true)
+            .headers(vec!["a".to_owned()])
+            .build();
+        assert!(matches!(result, Err(Error::ExcelConfigError(_))));
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .has_header(false)
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 1);
+        assert_eq!(
+            read_header(&config, &sheet).unwrap().names(),
+            ["col1", "col2", "col3"]
+        );
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .has_header(false)
+            .headers(["a", "b", "c"].map(String::from))
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 1);
+        assert_eq!(
+            read_header(&config, &sheet).unwrap().names(),
+            ["a", "b", "c"]
+        );
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .has_header(true)
+            .headers(["a", "b", "c"].map(String::from))
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 2);
+        let result = crate::config::ExcelConfigBuilder::new(path)
+            .has_header(true)
+            .headers(["a", "b", "c"].map(String::from))
+            .data_start_row(1)
+            .build();
+        assert!(matches!(result, Err(Error::ExcelConfigError(_))));
+        let result = crate::config::ExcelConfigBuilder::new(path)
+            .has_header(true)
+            .no_header(true)
+            .build();
+        assert!(matches!(result, Err(Error::ExcelConfigError(_))));
+    }
+
+    #[test]
+    fn test_header_row_gap() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut sheet = Range::new((0, 0), (5, 1));
+        sheet.set_value((0, 0), Data::String("title".to_owned()));
+        sheet.set_value((1, 0), Data::String("a".to_owned()));
+        sheet.set_value((1, 1), Data::String("b".to_owned()));
+        // Rows 3 and 4 are blank.
+        sheet.set_value((4, 0), Data::Int(1));
+        sheet.set_value((4, 1), Data::String("x".to_owned()));
+        sheet.set_value((5, 0), Data::Int(2));
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .header_row(2)
+            .data_start_row(5)
+            .build()
+            .unwrap();
+        let schema = read_header(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["a", "b"]);
+        assert_eq!(
+            schema.data_types(),
+            [ExcelDataType::Int, ExcelDataType::String]
+        );
+        assert!(!schema.columns[0].nullable);
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .header_row(2)
+            .build()
+            .unwrap();
+        assert_eq!(config.data_start_row(), 3);
+        assert!(
+            crate::config::ExcelConfigBuilder::new(path)
+                .header_row(3)
+                .data_start_row(3)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_header_count_mismatch() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut workbook = calamine::open_workbook_auto(path).unwrap();
+        let sheet = calamine::Reader::worksheet_range(&mut workbook, "Sheet1").unwrap();
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .headers(vec!["a".to_owned(), "b".to_owned()])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            read_header(&config, &sheet),
+            Err(Error::ExcelConfigError(message)) if message == "Found 2 headers for 5 data columns"
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_headers() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let headers = ["id", "Name", "Name", "active", "name"].map(String::from);
+        let mut workbook = calamine::open_workbook_auto(path).unwrap();
+        let sheet = calamine::Reader::worksheet_range(&mut workbook, "Sheet1").unwrap();
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .headers(headers.clone())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            read_header(&config, &sheet),
+            Err(Error::ExcelConfigError(message)) if message == "Duplicate column names: Name, name"
+        ));
+
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .headers(headers)
+            .dedup_headers(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            read_header(&config, &sheet).unwrap().names(),
+            ["id", "Name", "Name_2", "active", "name_3"]
+        );
+    }
+
+    #[test]
+    fn test_xls_encoding() {
+        // A BIFF5 file with cp1251 strings that claims code page 1252.
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cp1251.xls");
+        let builder = crate::config::ExcelConfigBuilder::new(path);
+
+        let config = builder.clone().build().unwrap();
+        let mut workbook = open_config_workbook(&config).unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        assert_eq!(sheet.get((1, 0)), Some(&Data::String("Èâàí".to_owned())));
+
+        let config = builder.xls_encoding("windows-1251").build().unwrap();
+        let mut workbook = open_config_workbook(&config).unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        assert_eq!(
+            read_header(&config, &sheet).unwrap().names(),
+            ["name", "city"]
+        );
+        assert_eq!(sheet.get((1, 0)), Some(&Data::String("Иван".to_owned())));
+        assert_eq!(sheet.get((2, 1)), Some(&Data::String("Казань".to_owned())));
+    }
+
+    #[test]
+    fn test_open_workbook() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        for name in ["simple.xlsx", "simple.xlsb", "simple.xls", "simple.ods"] {
+            let mut workbook = open_workbook(&fixtures.join(name), None).unwrap();
+            let sheet = calamine::Reader::worksheet_range(&mut workbook, "Sheet1").unwrap();
+            assert_eq!(sheet.headers().unwrap()[..2], ["id", "name"]);
+        }
+
+        assert!(matches!(
+            open_workbook(Path::new("report.numbers"), None),
+            Err(Error::ExcelConfigError(message)) if message == "Unsupported file format: .numbers"
+        ));
+        let fake = std::env::temp_dir().join("excel2sql-not-a-workbook.xlsx");
+        std::fs::write(&fake, "id,name\n").unwrap();
+        assert!(matches!(
+            open_workbook(&fake, None),
+            Err(Error::ExcelConfigError(_))
+        ));
+        std::fs::remove_file(fake).unwrap();
+        assert!(matches!(
+            open_workbook(Path::new("missing.xls"), None),
+            Err(Error::ExcelConfigError(message)) if message == "File not found: missing.xls"
+        ));
+        let dir = std::env::temp_dir().join("excel2sql-dir.xlsx");
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(matches!(
+            open_workbook(&dir, None),
+            Err(Error::ExcelConfigError(message)) if message.starts_with("Not a file")
+        ));
+        std::fs::remove_dir(dir).unwrap();
+    }
+
+    #[test]
+    fn test_coerce() {
+        let text = |s: &str| Data::String(s.to_owned());
+        assert!(!fits_type(&text("N/A"), ExcelDataType::Int));
+        assert!(fits_type(&text("12"), ExcelDataType::Int));
+        assert_eq!(
+            coerce(&text("1,234"), ExcelDataType::Int),
+            Some(Data::Int(1234))
+        );
+        assert_eq!(
+            coerce(&text("1 234.5"), ExcelDataType::Float),
+            Some(Data::Float(1234.5))
+        );
+        assert_eq!(
+            coerce(&text("Yes"), ExcelDataType::Bool),
+            Some(Data::Bool(true))
+        );
+        assert_eq!(coerce(&text("N/A"), ExcelDataType::Int), None);
+    }
+
+    #[test]
+    fn test_large_ints() {
+        let big = 5_000_000_000_i64;
+        assert!(fits_type(&Data::Float(big as f64), ExcelDataType::Int));
+        assert_eq!(Data::Float(big as f64).as_i64(), Some(big));
+        assert_eq!(
+            coerce(
+                &Data::String("5,000,000,000".to_owned()),
+                ExcelDataType::Int
+            ),
+            Some(Data::Int(big))
+        );
+        assert!(!fits_type(&Data::Float(1e30), ExcelDataType::Int));
+        assert_eq!(
+            coerce(&Data::String("1e30".to_owned()), ExcelDataType::Int),
+            None
+        );
+        assert_eq!(
+            crate::sql::sql_type(ExcelDataType::Int, crate::config::Database::Postgres),
+            "BIGINT"
+        );
+    }
+
+    #[test]
+    fn test_decimal_string() {
+        let text = |s: &str| Data::String(s.to_owned());
+        assert_eq!(decimal_string(&Data::Float(1e-7), 8).unwrap(), "0.00000010");
+        assert_eq!(decimal_string(&Data::Float(0.1 + 0.2), 2).unwrap(), "0.30");
+        assert_eq!(decimal_string(&Data::Int(42), 2).unwrap(), "42");
+        assert_eq!(
+            decimal_string(&text(" 12345678901234567.89 "), 2).unwrap(),
+            "12345678901234567.89"
+        );
+        assert_eq!(decimal_string(&text("1e5"), 2), None);
+        assert_eq!(decimal_string(&text("."), 2), None);
+
+        let decimal = ExcelDataType::Decimal {
+            precision: 10,
+            scale: 2,
+        };
+        assert_eq!(coerce(&text("1,234.50"), decimal), Some(text("1234.50")));
+    }
+
+    #[test]
+    fn test_sanitize_column_names() {
+        let headers = ["Unit Price ($)", "unit-price", "", "Größe", "ID"].map(String::from);
+        assert_eq!(
+            sanitize_column_names(&headers),
+            ["unit_price", "unit_price_2", "column", "gr_e", "id"]
+        );
+
+        let rename = HashMap::from([("ID".to_owned(), "Id".to_owned())]);
+        assert_eq!(
+            rename_header(
+                vec!["ID".to_owned(), "A B".to_owned()],
+                &rename,
+                false,
+                IdentifierCase::Preserve
+            ),
+            ["Id", "A B"]
+        );
+        assert_eq!(
+            rename_header(
+                vec!["ID".to_owned(), "A B".to_owned()],
+                &rename,
+                true,
+                IdentifierCase::Preserve
+            ),
+            ["Id", "a_b"]
+        );
+        assert_eq!(
+            rename_header(
+                vec!["ID".to_owned(), "A B".to_owned()],
+                &rename,
+                true,
+                IdentifierCase::Upper
+            ),
+            ["ID", "A_B"]
+        );
+    }
+
+    #[test]
+    fn test_header_type_hints() {
+        assert_eq!(
+            "Text".parse::<ExcelDataType>().unwrap(),
+            ExcelDataType::String
+        );
+        assert_eq!(
+            "decimal(10, 2)".parse::<ExcelDataType>().unwrap(),
+            ExcelDataType::Decimal {
+                precision: 10,
+                scale: 2
+            }
+        );
+        assert!("decimal(2,3)".parse::<ExcelDataType>().is_err());
+
+        let mut sheet = Range::new((0, 0), (1, 2));
+        sheet.set_value((0, 0), Data::String("amount:float".to_owned()));
+        sheet.set_value((0, 1), Data::String("active : bool".to_owned()));
+        sheet.set_value((0, 2), Data::String("name".to_owned()));
+        sheet.set_value((1, 0), Data::Int(1));
+        sheet.set_value((1, 1), Data::String("yes".to_owned()));
+        sheet.set_value((1, 2), Data::String("a".to_owned()));
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .header_type_hints(true)
+            .build()
+            .unwrap();
+        let (schema, _) = read_schema(&config, &sheet).unwrap();
+        assert_eq!(schema.names(), ["amount", "active", "name"]);
+        assert_eq!(
+            schema.data_types(),
+            [
+                ExcelDataType::Float,
+                ExcelDataType::Bool,
+                ExcelDataType::String
+            ]
+        );
+
+        sheet.set_value((0, 2), Data::String("name:money".to_owned()));
+        assert_eq!(
+            read_schema(&config, &sheet).unwrap_err().to_string(),
+            "Excel Config Error: Unknown column type `money` in header `name:money`."
+        );
+    }
+
+    #[test]
+    fn test_infer_nullable() {
+        let mut sheet = Range::new((0, 0), (2, 2));
+        sheet.set_value((1, 0), Data::Int(1));
+        sheet.set_value((2, 0), Data::Int(2));
+        sheet.set_value((1, 1), Data::String(String::new()));
+        sheet.set_value((2, 1), Data::Int(2));
+        sheet.set_value((1, 2), Data::Int(1));
+
+        let col_types = [
+            ExcelDataType::Int,
+            ExcelDataType::String,
+            ExcelDataType::Int,
+        ];
+        assert_eq!(
+            infer_nullable(&sheet, 2, &col_types, true, &BoolStrings::default()),
+            vec![false, true, true]
+        );
+        assert_eq!(
+            infer_nullable(&sheet, 2, &col_types, false, &BoolStrings::default()),
+            vec![false, false, true]
+        );
+        let col_types = [
+            ExcelDataType::Bool,
+            ExcelDataType::String,
+            ExcelDataType::Int,
+        ];
+        assert_eq!(
+            infer_nullable(&sheet, 2, &col_types, false, &BoolStrings::default()),
+            vec![true, false, true]
+        );
+    }
+
+    #[test]
+    fn test_cell_range() {
+        let mut sheet = Range::new((0, 0), (2, 2));
+        sheet.set_value((0, 1), Data::String("b".to_owned()));
+        sheet.set_value((1, 1), Data::Int(1));
+        sheet.set_value((2, 0), Data::Int(2));
+
+        let range = cell_range(sheet.clone(), Some(Dimensions::new((0, 1), (9, 2))));
+        assert_eq!(range.start(), Some((0, 1)));
+        assert_eq!(range.end(), Some((1, 2)));
+        assert_eq!(range.get((1, 0)), Some(&Data::Int(1)));
+        assert!(cell_range(sheet, Some(Dimensions::new((5, 5), (6, 6)))).is_empty());
+    }
+
+    #[test]
+    fn test_missing_sheet() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let error = sheet_range(&mut workbook, "Sheet2", None, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Excel Config Error: Sheet `Sheet2` not found; available: Sheet1, Other."
+        );
+    }
+
+    #[test]
+    fn test_fill_merged_cells() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/merged.xlsx");
+        let mut workbook = open_workbook(Path::new(path), None).unwrap();
+        let sheet = sheet_range(&mut workbook, "Sheet1", None, true).unwrap();
+        let column =
+            |col: usize| -> Vec<String> { sheet.rows().map(|row| row[col].to_string()).collect() };
+        assert_eq!(column(0), ["category", "category", "fruit", "fruit", "veg"]);
+        assert_eq!(column(2), ["item", "price", "1", "2", "3"]);
+
+        let sheet = sheet_range(&mut workbook, "Sheet1", None, false).unwrap();
+        assert_eq!(sheet.get((3, 0)), Some(&Data::Empty));
+    }
+
+    #[test]
+    fn test_infer_column_types() {
+        let mut sheet = Range::new((0, 0), (3, 2));
+        sheet.set_value((0, 0), Data::String("a".to_owned()));
+        sheet.set_value((0, 1), Data::String("b".to_owned()));
+        sheet.set_value((0, 2), Data::String("c".to_owned()));
+        sheet.set_value((1, 0), Data::Int(1));
+        sheet.set_value((2, 0), Data::Float(1.5));
+        sheet.set_value((2, 1), Data::Int(2));
+        sheet.set_value((1, 2), Data::Int(1));
+        sheet.set_value((3, 2), Data::String("x".to_owned()));
+
+        assert_eq!(
+            infer_column_types(&sheet, 2, 100, false),
+            vec![
+                ExcelDataType::Float,
+                ExcelDataType::Int,
+                ExcelDataType::String
+            ]
+        );
+        assert_eq!(
+            infer_column_types(&sheet, 2, 1, false),
+            vec![ExcelDataType::Int, ExcelDataType::NULL, ExcelDataType::Int]
+        );
+
+        // xlsx stores whole numbers as doubles.
+        assert_eq!(infer_type(&Data::Float(42.0)), ExcelDataType::Int);
+        assert_eq!(infer_type(&Data::Float(1e300)), ExcelDataType::Float);
+        assert!(fits_type(&Data::Float(42.0), ExcelDataType::Int));
+        assert!(!fits_type(&Data::Float(0.5), ExcelDataType::Int));
+    }
+
+    #[test]
+    fn test_numeric_strings() {
+        use crate::value::CellValue;
+
+        let text = |s: &str| Data::String(s.to_owned());
+        let mut sheet = Range::new((0, 0), (3, 4));
+        for (row, values) in [
+            ["12", "1.5", "007", "3", "-4"],
+            ["-3", "2", "12", "N/A", "1e3"],
+            ["40", "", "9", "7", "+2"],
+        ]
+        .iter()
+        .enumerate()
+        {
+            for (col, value) in values.iter().enumerate() {
+                sheet.set_value((row as u32 + 1, col as u32), text(value));
+            }
+        }
+
+        assert_eq!(
+            infer_column_types(&sheet, 2, 100, false),
+            vec![ExcelDataType::String; 5]
+        );
+        assert_eq!(
+            infer_column_types(&sheet, 2, 100, true),
+            vec![
+                ExcelDataType::Int,
+                ExcelDataType::Float,
+                ExcelDataType::String,
+                ExcelDataType::String,
+                ExcelDataType::Float
+            ]
+        );
+        assert_eq!(numeric_string_type(&text("0")), ExcelDataType::Int);
+        assert_eq!(numeric_string_type(&text("0.25")), ExcelDataType::Float);
+        assert_eq!(numeric_string_type(&text("inf")), ExcelDataType::String);
+        assert_eq!(numeric_string_type(&text("1 000")), ExcelDataType::String);
+        assert_eq!(
+            CellValue::from_data(&text("-3"), ExcelDataType::Int, true),
+            CellValue::Int(-3)
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_inference() {
+        let rows = 20_000;
+        let mut sheet = Range::new((0, 0), (rows, 3));
+        for row in 1..=rows {
+            sheet.set_value((row, 0), Data::Int(row as i64));
+            if row % 7 != 0 {
+                sheet.set_value((row, 1), Data::Float(row as f64 / 2.0));
+            }
+            sheet.set_value((row, 2), Data::Int(1));
+        }
+        sheet.set_value((rows - 3, 2), Data::String("x".to_owned()));
+        sheet.set_value((9000, 3), Data::Bool(true));
+
+        let scan = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let col_types = infer_column_types(&sheet, 2, usize::MAX, false);
+                let nullable = infer_nullable(&sheet, 2, &col_types, true, &BoolStrings::default());
+                (col_types, nullable)
+            })
+        };
+        let (col_types, nullable) = scan(1);
+        assert_eq!(
+            col_types,
+            [
+                ExcelDataType::Int,
+                ExcelDataType::Float,
+                ExcelDataType::String,
+                ExcelDataType::Bool
+            ]
+        );
+        assert_eq!(nullable, [false, true, false, true]);
+        for threads in [2, 8] {
+            assert_eq!(scan(threads), (col_types.clone(), nullable.clone()));
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            assert_eq!(chunk_size(100), 32);
+            assert_eq!(chunk_size(20_000), 5_000);
+            assert_eq!(chunk_size(10), 32);
+        });
+    }
+
+    #[test]
+    fn test_datetime_string() {
+        let dt = |value, is_1904| {
+            Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::DateTime,
+                is_1904,
+            ))
+        };
+        assert_eq!(
+            datetime_string(&dt(45000.5, false)).as_deref(),
+            Some("2023-03-15T12:00:00")
+        );
+        assert_eq!(
+            datetime_string(&dt(43538.5, true)).as_deref(),
+            Some("2023-03-15T12:00:00")
+        );
+        assert_eq!(
+            datetime_string(&dt(0.25, false)).as_deref(),
+            Some("1899-12-31T06:00:00")
+        );
+        assert_eq!(
+            datetime_string(&Data::Float(45000.0)).as_deref(),
+            Some("2023-03-15T00:00:00")
+        );
+    }
+
+    #[test]
+    fn test_date_and_time_types() {
+        let dt = |value| {
+            Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::DateTime,
+                false,
+            ))
+        };
+        assert_eq!(infer_type(&dt(45000.0)), ExcelDataType::Date);
+        assert_eq!(infer_type(&dt(0.25)), ExcelDataType::Time);
+        assert_eq!(infer_type(&dt(45000.5)), ExcelDataType::DateTime);
+        assert_eq!(
+            infer_type(&Data::DateTimeIso("2023-03-15".to_owned())),
+            ExcelDataType::Date
+        );
+        assert_eq!(
+            infer_type(&Data::DateTimeIso("2023-03-15T12:00:00".to_owned())),
+            ExcelDataType::DateTime
+        );
+        assert_eq!(
+            promote(ExcelDataType::Date, ExcelDataType::DateTime),
+            ExcelDataType::DateTime
+        );
+        assert_eq!(date_string(&dt(45000.5)).as_deref(), Some("2023-03-15"));
+        assert_eq!(time_string(&dt(0.25)).as_deref(), Some("06:00:00"));
+
+        let text = |s: &str| Data::String(s.to_owned());
+        assert_eq!(
+            datetime_string(&text("2023-03-15 08:30")).as_deref(),
+            Some("2023-03-15T08:30:00")
+        );
+        assert_eq!(
+            datetime_string(&text("2023-03-15T08:30:00+02:00")).as_deref(),
+            Some("2023-03-15T06:30:00")
+        );
+        assert_eq!(
+            date_string(&text("2023-03-15")).as_deref(),
+            Some("2023-03-15")
+        );
+        assert_eq!(
+            time_string(&text("08:30:15.5")).as_deref(),
+            Some("08:30:15.500")
+        );
+        for value in ["N/A", "2023-13-01", "soon", ""] {
+            assert_eq!(datetime_string(&text(value)), None, "{}", value);
+            assert!(!fits_type(&text(value), ExcelDataType::Date), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_durations() {
+        let elapsed = Data::DateTime(ExcelDateTime::new(1.5, ExcelDateTimeType::TimeDelta, false));
+        let iso = Data::DurationIso("PT36H30M15.5S".to_owned());
+        assert_eq!(infer_type(&elapsed), ExcelDataType::Duration);
+        assert_eq!(infer_type(&iso), ExcelDataType::Duration);
+        assert_eq!(duration_string(&elapsed).as_deref(), Some("36:00:00"));
+        let text = |s: &str| Data::String(s.to_owned());
+        assert_eq!(
+            duration_string(&text("36:30:15.5")).as_deref(),
+            Some("36:30:15.500")
+        );
+        assert_eq!(duration_string(&text("-1:05")).as_deref(), Some("-1:05:00"));
+        assert_eq!(duration_string(&text("PT1H")).as_deref(), Some("1:00:00"));
+        for value in ["N/A", "1:75:00", "1.5:00", ""] {
+            assert!(
+                !fits_type(&text(value), ExcelDataType::Duration),
+                "{}",
+                value
+            );
+        }
+        assert_eq!(duration_string(&iso).as_deref(), Some("36:30:15.500"));
+        assert_eq!(
+            duration_string(&Data::DurationIso("-P1DT2H".to_owned())).as_deref(),
+            Some("-26:00:00")
+        );
+        assert_eq!(duration(&Data::DurationIso("PT1X".to_owned())), None);
+        assert_eq!(duration(&Data::Float(1.5)), None);
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .durations_as_seconds(true)
+            .build()
+            .unwrap();
+        let mut row = [elapsed, iso, Data::Float(1.5)];
+        clean_row(&mut row, &config);
+        assert_eq!(
+            row,
+            [
+                Data::Float(129_600.0),
+                Data::Float(131_415.5),
+                Data::Float(1.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_text() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simple.xlsx");
+        let config = crate::config::ExcelConfigBuilder::new(path)
+            .all_text(true)
+            .build()
+            .unwrap();
+        let date = ExcelDateTime::new(45000.0, ExcelDateTimeType::DateTime, false);
+        let mut row = [
+            Data::Int(42),
+            Data::Float(0.1),
+            Data::Bool(true),
+            Data::DateTime(date),
+            Data::String("x".to_owned()),
+            Data::Empty,
+        ];
+        clean_row(&mut row, &config);
+        assert_eq!(
+            row,
+            [
+                Data::String("42".to_owned()),
+                Data::String("0.1".to_owned()),
+                Data::String("true".to_owned()),
+                Data::String("2023-03-15".to_owned()),
+                Data::String("x".to_owned()),
+                Data::Empty,
+            ]
+        );
+
+        let mut workbook = open_config_workbook(&config).unwrap();
+        let sheet = read_sheet(&mut workbook, &config).unwrap();
+        let (schema, _) = read_schema(&config, &sheet).unwrap();
+        assert!(
+            schema
+                .columns
+                .iter()
+                .all(|c| c.data_type == ExcelDataType::String)
+        );
     }
 }
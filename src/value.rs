@@ -0,0 +1,162 @@
+use crate::utils::{
+    ExcelDataType, date_string, datetime_string, decimal_string, duration_string, is_null,
+    time_string,
+};
+use calamine::{Data, DataType};
+use sqlx::{Any, Database, Encode, Type, any::AnyTypeInfo, encode::IsNull, error::BoxDynError};
+
+/// A cell converted to the type of its column, the one value every sink
+/// binds, quotes or prints.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CellValue {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    /// ISO text of a date, time, datetime or duration.
+    DateTime(String),
+    /// Fixed point text, rounded to the scale of the column.
+    Decimal(String),
+    /// Keeps the column type, drivers want a typed NULL.
+    Null(ExcelDataType),
+}
+
+impl CellValue {
+    /// Converts `data` for a `data_type` column. Empty cells and cells that
+    /// do not convert become NULL.
+    pub(crate) fn from_data(
+        data: &Data,
+        data_type: ExcelDataType,
+        empty_string_as_null: bool,
+    ) -> Self {
+        if is_null(data, empty_string_as_null) {
+            return CellValue::Null(data_type);
+        }
+
+        let value = match data_type {
+            ExcelDataType::Int => data.as_i64().map(CellValue::Int),
+            ExcelDataType::Float => data.as_f64().map(CellValue::Float),
+            ExcelDataType::Bool => data.get_bool().map(CellValue::Bool),
+            ExcelDataType::String | ExcelDataType::NULL => data.as_string().map(CellValue::Str),
+            ExcelDataType::Date => date_string(data).map(CellValue::DateTime),
+            ExcelDataType::Time => time_string(data).map(CellValue::DateTime),
+            ExcelDataType::DateTime => datetime_string(data).map(CellValue::DateTime),
+            ExcelDataType::Duration => duration_string(data).map(CellValue::DateTime),
+            ExcelDataType::Decimal { scale, .. } => {
+                decimal_string(data, scale).map(CellValue::Decimal)
+            }
+        };
+        value.unwrap_or(CellValue::Null(data_type))
+    }
+
+    /// The value as text, `None` for NULL.
+    pub(crate) fn text(&self) -> Option<String> {
+        match self {
+            CellValue::Int(v) => Some(v.to_string()),
+            CellValue::Float(v) => Some(v.to_string()),
+            CellValue::Bool(v) => Some(v.to_string()),
+            CellValue::Str(v) | CellValue::DateTime(v) | CellValue::Decimal(v) => Some(v.clone()),
+            CellValue::Null(_) => None,
+        }
+    }
+}
+
+impl Type<Any> for CellValue {
+    fn type_info() -> AnyTypeInfo {
+        <String as Type<Any>>::type_info()
+    }
+
+    fn compatible(_: &AnyTypeInfo) -> bool {
+        true
+    }
+}
+
+impl<'q> Encode<'q, Any> for CellValue {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Any as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        match self {
+            CellValue::Int(v) => <i64 as Encode<Any>>::encode(*v, buf),
+            CellValue::Float(v) => <f64 as Encode<Any>>::encode(*v, buf),
+            CellValue::Bool(v) => <bool as Encode<Any>>::encode(*v, buf),
+            CellValue::Str(v) | CellValue::DateTime(v) | CellValue::Decimal(v) => {
+                <String as Encode<Any>>::encode(v.clone(), buf)
+            }
+            CellValue::Null(ExcelDataType::Int) => <Option<i64> as Encode<Any>>::encode(None, buf),
+            CellValue::Null(ExcelDataType::Float) => {
+                <Option<f64> as Encode<Any>>::encode(None, buf)
+            }
+            CellValue::Null(ExcelDataType::Bool) => {
+                <Option<bool> as Encode<Any>>::encode(None, buf)
+            }
+            CellValue::Null(_) => <Option<String> as Encode<Any>>::encode(None, buf),
+        }
+    }
+
+    fn produces(&self) -> Option<AnyTypeInfo> {
+        Some(match self {
+            CellValue::Int(_) | CellValue::Null(ExcelDataType::Int) => {
+                <i64 as Type<Any>>::type_info()
+            }
+            CellValue::Float(_) | CellValue::Null(ExcelDataType::Float) => {
+                <f64 as Type<Any>>::type_info()
+            }
+            CellValue::Bool(_) | CellValue::Null(ExcelDataType::Bool) => {
+                <bool as Type<Any>>::type_info()
+            }
+            _ => <String as Type<Any>>::type_info(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Arguments, any::AnyArguments};
+
+    #[test]
+    fn test_from_data() {
+        let text = |s: &str| Data::String(s.to_owned());
+        assert_eq!(
+            CellValue::from_data(&Data::Float(3.0), ExcelDataType::Int, true),
+            CellValue::Int(3)
+        );
+        assert_eq!(
+            CellValue::from_data(&text(""), ExcelDataType::String, true),
+            CellValue::Null(ExcelDataType::String)
+        );
+        assert_eq!(
+            CellValue::from_data(&text(""), ExcelDataType::String, false),
+            CellValue::Str(String::new())
+        );
+        assert_eq!(
+            CellValue::from_data(&Data::Float(45000.5), ExcelDataType::DateTime, true),
+            CellValue::DateTime("2023-03-15T12:00:00".to_owned())
+        );
+        assert_eq!(
+            CellValue::from_data(&text("N/A"), ExcelDataType::Float, true),
+            CellValue::Null(ExcelDataType::Float)
+        );
+        assert_eq!(
+            CellValue::from_data(
+                &Data::Float(1.005),
+                ExcelDataType::Decimal {
+                    precision: 10,
+                    scale: 1
+                },
+                true
+            )
+            .text()
+            .as_deref(),
+            Some("1.0")
+        );
+
+        let value = CellValue::Null(ExcelDataType::Int);
+        assert_eq!(value.produces(), Some(<i64 as Type<Any>>::type_info()));
+        let mut arguments = AnyArguments::default();
+        arguments.add(value).unwrap();
+        arguments.add(CellValue::Bool(true)).unwrap();
+        assert_eq!(arguments.len(), 2);
+    }
+}
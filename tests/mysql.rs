@@ -0,0 +1,74 @@
+//! Loads into the MySQL database at `EXCEL2SQL_MYSQL_URL`, skipped when it is
+//! not set.
+#![cfg(feature = "mysql")]
+
+use calamine::{Data, Range};
+use excel2sql::config::{CreateMode, DatabaseConfig, ExcelConfigBuilder, LoadConfigBuilder};
+use sqlx::AnyPool;
+
+async fn database() -> Option<(DatabaseConfig, AnyPool)> {
+    let Ok(url) = std::env::var("EXCEL2SQL_MYSQL_URL") else {
+        eprintln!("EXCEL2SQL_MYSQL_URL is not set, skipping");
+        return None;
+    };
+    let db_config = DatabaseConfig::new(&url).unwrap();
+    let pool = db_config.connect().await.unwrap();
+    Some((db_config, pool))
+}
+
+// A sheet of `rows` under the header `names`.
+fn sheet(names: &[&str], rows: &[Vec<Data>]) -> Range<Data> {
+    let mut range = Range::new((0, 0), (rows.len() as u32, names.len() as u32 - 1));
+    for (col, name) in names.iter().enumerate() {
+        range.set_value((0, col as u32), Data::String(name.to_string()));
+    }
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            range.set_value((row as u32 + 1, col as u32), value.clone());
+        }
+    }
+    range
+}
+
+#[tokio::test]
+async fn test_repeat_load_with_indexes() {
+    let Some((db_config, pool)) = database().await else {
+        return;
+    };
+    let excel_config = ExcelConfigBuilder::for_range()
+        .table_name("repeat_indexed")
+        .index(vec!["name".to_owned()])
+        .build()
+        .unwrap();
+    let rows = (0..3)
+        .map(|id| vec![Data::Int(id), Data::String(format!("row {}", id))])
+        .collect::<Vec<_>>();
+    let range = sheet(&["id", "name"], &rows);
+    let modes = [
+        CreateMode::DropAndCreate,
+        CreateMode::IfNotExists,
+        CreateMode::TruncateExisting,
+    ];
+    for create_mode in modes {
+        let load_config = LoadConfigBuilder::new()
+            .create_mode(create_mode)
+            .build()
+            .unwrap();
+        excel2sql::load_range(&range, &excel_config, &db_config, &load_config)
+            .await
+            .unwrap();
+    }
+    let indexes: i64 = sqlx::query_scalar(
+        "SELECT COUNT(DISTINCT index_name) FROM information_schema.statistics \
+         WHERE table_schema = DATABASE() AND table_name = 'repeat_indexed'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(indexes, 1);
+    let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM repeat_indexed")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, 3);
+}
@@ -0,0 +1,160 @@
+//! Loads into the Postgres database at `EXCEL2SQL_POSTGRES_URL`, skipped when
+//! it is not set.
+#![cfg(feature = "postgres")]
+
+use calamine::{Data, Range};
+use excel2sql::config::{
+    CreateMode, DatabaseConfig, ExcelConfigBuilder, LoadConfigBuilder, Verify,
+};
+use sqlx::AnyPool;
+use std::collections::HashMap;
+
+async fn database() -> Option<(DatabaseConfig, AnyPool)> {
+    let Ok(url) = std::env::var("EXCEL2SQL_POSTGRES_URL") else {
+        eprintln!("EXCEL2SQL_POSTGRES_URL is not set, skipping");
+        return None;
+    };
+    let db_config = DatabaseConfig::new(&url).unwrap();
+    let pool = db_config.connect().await.unwrap();
+    Some((db_config, pool))
+}
+
+// A sheet of `rows` under the header `names`.
+fn sheet(names: &[&str], rows: &[Vec<Data>]) -> Range<Data> {
+    let mut range = Range::new((0, 0), (rows.len() as u32, names.len() as u32 - 1));
+    for (col, name) in names.iter().enumerate() {
+        range.set_value((0, col as u32), Data::String(name.to_string()));
+    }
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            range.set_value((row as u32 + 1, col as u32), value.clone());
+        }
+    }
+    range
+}
+
+async fn count(pool: &AnyPool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", table))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_failed_copy_keeps_table() {
+    let Some((db_config, pool)) = database().await else {
+        return;
+    };
+    let modes = [
+        ("copy_drop", CreateMode::DropAndCreate),
+        ("copy_truncate", CreateMode::TruncateExisting),
+    ];
+    for (table, create_mode) in modes {
+        let excel_config = ExcelConfigBuilder::for_range()
+            .table_name(table)
+            .primary_key(vec!["id".to_owned()])
+            .build()
+            .unwrap();
+        let load_config = LoadConfigBuilder::new()
+            .copy(true)
+            .create_mode(create_mode)
+            .build()
+            .unwrap();
+        let rows = [1, 2].map(|id| vec![Data::Int(id), Data::String(format!("row {}", id))]);
+        let report = excel2sql::load_range(
+            &sheet(&["id", "name"], &rows),
+            &excel_config,
+            &db_config,
+            &load_config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.rows_inserted, 2);
+
+        // The duplicate key fails the COPY after the table was set up again.
+        let rows = [3, 3].map(|id| vec![Data::Int(id), Data::String(format!("row {}", id))]);
+        let result = excel2sql::load_range(
+            &sheet(&["id", "name"], &rows),
+            &excel_config,
+            &db_config,
+            &load_config,
+        )
+        .await;
+        assert!(result.is_err(), "{}", table);
+        assert_eq!(count(&pool, table).await, 2, "{}", table);
+    }
+}
+
+#[tokio::test]
+async fn test_copy_indexes_and_verify() {
+    let Some((db_config, pool)) = database().await else {
+        return;
+    };
+    let excel_config = ExcelConfigBuilder::for_range()
+        .table_name("copy_indexed")
+        .index(vec!["name".to_owned()])
+        .build()
+        .unwrap();
+    let load_config = LoadConfigBuilder::new()
+        .copy(true)
+        .create_mode(CreateMode::DropAndCreate)
+        .verify(Verify::Strict)
+        .build()
+        .unwrap();
+    let rows = (0..10)
+        .map(|id| vec![Data::Int(id), Data::String(format!("row {}", id))])
+        .collect::<Vec<_>>();
+    let report = excel2sql::load_range(
+        &sheet(&["id", "name"], &rows),
+        &excel_config,
+        &db_config,
+        &load_config,
+    )
+    .await
+    .unwrap();
+    assert_eq!(report.rows_counted, Some(10));
+    let indexes: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM pg_indexes WHERE tablename = 'copy_indexed'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(indexes, 1);
+}
+
+#[tokio::test]
+async fn test_column_defaults() {
+    let Some((db_config, pool)) = database().await else {
+        return;
+    };
+    let excel_config = ExcelConfigBuilder::for_range()
+        .table_name("copy_defaults")
+        .column_defaults(HashMap::from([("status".to_owned(), "pending".to_owned())]))
+        .build()
+        .unwrap();
+    let rows = [
+        vec![Data::Int(1), Data::String("sent".to_owned())],
+        vec![Data::Int(2), Data::Empty],
+        vec![Data::Int(3), Data::String("paid".to_owned())],
+    ];
+    for copy in [true, false] {
+        let load_config = LoadConfigBuilder::new()
+            .copy(copy)
+            .create_mode(CreateMode::DropAndCreate)
+            .build()
+            .unwrap();
+        excel2sql::load_range(
+            &sheet(&["id", "status"], &rows),
+            &excel_config,
+            &db_config,
+            &load_config,
+        )
+        .await
+        .unwrap();
+        let statuses: Vec<String> =
+            sqlx::query_scalar("SELECT status FROM copy_defaults ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(statuses, ["sent", "pending", "paid"], "copy {}", copy);
+    }
+}